@@ -0,0 +1,95 @@
+// Связь "один ко многим": загрузка дочерних записей для списка родителей одним запросом
+// (аналог <collection> в MyBatis); реализуется #[derive(SqlResult)] для поля с атрибутом has_many
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+use rusqlite::types::{FromSql, ToSql};
+use rusqlite::Connection;
+
+use crate::{backend, convert, SqlResult};
+
+// Количество ключей родителей в одном запросе; меньше ограничения SQLite на число параметров
+const KEYS_PER_QUERY: usize = 500;
+
+pub trait HasMany<C> {
+    // Тип первичного ключа родителя и внешнего ключа дочерней таблицы
+    type Key: ToSql + FromSql + Eq + Hash;
+
+    // Столбец внешнего ключа в дочерней таблице
+    fn foreign_key() -> &'static str;
+
+    // Порядок дочерних записей у родителя (выражение ORDER BY); по-умолчанию - порядок вставки
+    fn order_by() -> &'static str {
+        "rowid"
+    }
+
+    fn key(&self) -> &Self::Key;
+
+    fn children_mut(&mut self) -> &mut Vec<C>;
+}
+
+// Дочерние строки таблицы `table` в порядке HasMany::order_by добавляются к родителям
+// с соответствующим ключом;
+// если у нескольких родителей одинаковый ключ, каждый получает копии строк
+// Выбираются все столбцы таблицы (SELECT *), поэтому C с deny_unknown_columns должна
+// отображать и внешний ключ, и остальные столбцы таблицы
+pub fn load_has_many<C, P>(
+    conn: &Connection,
+    parents: &mut [P],
+    table: &str,
+) -> rusqlite::Result<()>
+where
+    C: SqlResult + Clone,
+    P: HasMany<C>,
+{
+    let mut children: HashMap<P::Key, Vec<C>> = HashMap::new();
+    {
+        let mut seen = HashSet::new();
+        let keys: Vec<&P::Key> = parents
+            .iter()
+            .map(HasMany::key)
+            .filter(|&key| seen.insert(key))
+            .collect();
+        for keys in keys.chunks(KEYS_PER_QUERY) {
+            let params = convert::expanded_params(":key", keys);
+            let names: Vec<&str> = params.iter().map(|(name, _)| name.as_str()).collect();
+            let sql = format!(
+                "SELECT * FROM {} WHERE {} IN ({}) ORDER BY {}",
+                table,
+                P::foreign_key(),
+                names.join(", "),
+                P::order_by()
+            );
+            let mut stmt = conn.prepare(&sql)?;
+            C::check_columns(&stmt)?;
+            let indices = C::column_indices(&stmt);
+            let params: Vec<(&str, &dyn ToSql)> = params
+                .iter()
+                .map(|(name, value)| (name.as_str(), *value))
+                .collect();
+            let mut rows = backend::query(&mut stmt, &params)?;
+            while let Some(row) = rows.next()? {
+                let key = convert::get_with(row, P::foreign_key(), P::Key::column_result)?;
+                let child = C::from_row_indexed(row, &indices)?;
+                children.entry(key).or_default().push(child);
+            }
+        }
+    }
+    // строки перемещаются к последнему родителю с ключом, предыдущие получают копии
+    let mut last = HashMap::new();
+    for (i, parent) in parents.iter().enumerate() {
+        last.insert(parent.key(), i);
+    }
+    let last: HashSet<usize> = last.into_values().collect();
+    for (i, parent) in parents.iter_mut().enumerate() {
+        let xs = if last.contains(&i) {
+            children.remove(parent.key())
+        } else {
+            children.get(parent.key()).cloned()
+        };
+        if let Some(xs) = xs {
+            parent.children_mut().extend(xs);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,291 @@
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::{mpsc, Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use rusqlite::types::{ToSql, ToSqlOutput, Value};
+use rusqlite::{ffi, Connection};
+
+use crate::{
+    backend, BatiskafConnection, HasMany, MappingReport, SqlDelete, SqlInsert, SqlParam, SqlResult,
+    SqlSchema, SqlUpdate,
+};
+
+type Job = Box<dyn FnOnce(&mut Connection) + Send>;
+
+// Соединение, которое живёт в отдельном потоке; запросы выполняются в этом потоке,
+// не блокируя исполнителя асинхронных задач
+pub struct AsyncConnection {
+    sender: mpsc::Sender<Job>,
+}
+
+// Методы не заимствуют соединение и аргументы: параметры преобразуются в значения при вызове,
+// поэтому возвращаемые future можно передавать в другие потоки (tokio::spawn)
+impl AsyncConnection {
+    pub fn new(conn: Connection) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        thread::spawn(move || {
+            let mut conn = conn;
+            for job in receiver {
+                // паника завершает только свой вызов: получатель результата получит ошибку
+                let _ = panic::catch_unwind(AssertUnwindSafe(|| job(&mut conn)));
+            }
+        });
+        AsyncConnection { sender }
+    }
+
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+    ) -> impl Future<Output = rusqlite::Result<Self>> + Send + 'static {
+        let path = path.as_ref().to_path_buf();
+        async move {
+            let (tx, rx) = oneshot();
+            thread::spawn(move || tx.send(Connection::open(path)));
+            rx.await
+                .unwrap_or_else(|| Err(terminated()))
+                .map(AsyncConnection::new)
+        }
+    }
+
+    pub async fn open_in_memory() -> rusqlite::Result<Self> {
+        Connection::open_in_memory().map(AsyncConnection::new)
+    }
+
+    // Выполняет произвольную функцию в потоке соединения
+    pub fn call<F, R>(&self, f: F) -> impl Future<Output = rusqlite::Result<R>> + Send + 'static
+    where
+        F: FnOnce(&mut Connection) -> rusqlite::Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let sender = self.sender.clone();
+        async move {
+            let (tx, rx) = oneshot();
+            let job: Job = Box::new(move |conn| tx.send(f(conn)));
+            if sender.send(job).is_err() {
+                return Err(terminated());
+            }
+            rx.await.unwrap_or_else(|| Err(panicked()))
+        }
+    }
+
+    pub fn select_one<T: SqlResult + Send + 'static>(
+        &self,
+        sql: &str,
+        params: &[(&str, &dyn ToSql)],
+    ) -> impl Future<Output = rusqlite::Result<T>> + Send + 'static {
+        let sql = sql.to_string();
+        let params = owned_params(params);
+        self.call(move |conn| conn.select_one(&sql, &borrowed_params(&params?)))
+    }
+
+    pub fn select_many<T: SqlResult + Send + 'static>(
+        &self,
+        sql: &str,
+        params: &[(&str, &dyn ToSql)],
+    ) -> impl Future<Output = rusqlite::Result<Vec<T>>> + Send + 'static {
+        let sql = sql.to_string();
+        let params = owned_params(params);
+        self.call(move |conn| conn.select_many(&sql, &borrowed_params(&params?)))
+    }
+
+    // Функции `*_with` принимают значение SqlParam по значению: параметры запроса зависят
+    // от подготовленного запроса и вычисляются в потоке соединения
+    pub fn select_one_with<T, P>(
+        &self,
+        sql: &str,
+        params: P,
+    ) -> impl Future<Output = rusqlite::Result<T>> + Send + 'static
+    where
+        T: SqlResult + Send + 'static,
+        P: SqlParam + Send + 'static,
+    {
+        let sql = sql.to_string();
+        self.call(move |conn| conn.select_one_with(&sql, &params))
+    }
+
+    pub fn select_many_with<T, P>(
+        &self,
+        sql: &str,
+        params: P,
+    ) -> impl Future<Output = rusqlite::Result<Vec<T>>> + Send + 'static
+    where
+        T: SqlResult + Send + 'static,
+        P: SqlParam + Send + 'static,
+    {
+        let sql = sql.to_string();
+        self.call(move |conn| conn.select_many_with(&sql, &params))
+    }
+
+    pub fn execute_with<P>(
+        &self,
+        sql: &str,
+        params: P,
+    ) -> impl Future<Output = rusqlite::Result<usize>> + Send + 'static
+    where
+        P: SqlParam + Send + 'static,
+    {
+        let sql = sql.to_string();
+        self.call(move |conn| conn.execute_with(&sql, &params))
+    }
+
+    pub fn insert<T>(
+        &self,
+        table: &str,
+        value: T,
+    ) -> impl Future<Output = rusqlite::Result<i64>> + Send + 'static
+    where
+        T: SqlInsert + SqlParam + Send + 'static,
+    {
+        let table = table.to_string();
+        self.call(move |conn| conn.insert(&table, &value))
+    }
+
+    pub fn update<T>(
+        &self,
+        table: &str,
+        value: T,
+    ) -> impl Future<Output = rusqlite::Result<usize>> + Send + 'static
+    where
+        T: SqlUpdate + SqlParam + Send + 'static,
+    {
+        let table = table.to_string();
+        self.call(move |conn| conn.update(&table, &value))
+    }
+
+    pub fn delete<T>(
+        &self,
+        table: &str,
+        value: T,
+    ) -> impl Future<Output = rusqlite::Result<usize>> + Send + 'static
+    where
+        T: SqlDelete + SqlParam + Send + 'static,
+    {
+        let table = table.to_string();
+        self.call(move |conn| conn.delete(&table, &value))
+    }
+
+    // Родители передаются по значению и возвращаются с загруженными дочерними записями
+    pub fn load_has_many<C, P>(
+        &self,
+        parents: Vec<P>,
+        table: &str,
+    ) -> impl Future<Output = rusqlite::Result<Vec<P>>> + Send + 'static
+    where
+        C: SqlResult + Clone + 'static,
+        P: HasMany<C> + Send + 'static,
+    {
+        let table = table.to_string();
+        self.call(move |conn| {
+            let mut parents = parents;
+            conn.load_has_many::<C, P>(&mut parents, &table)?;
+            Ok(parents)
+        })
+    }
+
+    pub fn verify_mapping<T: SqlSchema + 'static>(
+        &self,
+        table: &str,
+    ) -> impl Future<Output = rusqlite::Result<MappingReport>> + Send + 'static {
+        let table = table.to_string();
+        self.call(move |conn| conn.verify_mapping::<T>(&table))
+    }
+}
+
+// Параметры передаются в поток соединения в виде значений
+fn owned_params(params: &[(&str, &dyn ToSql)]) -> rusqlite::Result<Vec<(String, Value)>> {
+    params
+        .iter()
+        .map(|&(name, value)| {
+            let value = match value.to_sql()? {
+                ToSqlOutput::Borrowed(v) => backend::to_value(v)?,
+                ToSqlOutput::Owned(v) => v,
+                #[allow(unreachable_patterns)]
+                _ => {
+                    return Err(rusqlite::Error::ToSqlConversionFailure(
+                        format!("unsupported value of parameter `{}`", name).into(),
+                    ))
+                }
+            };
+            Ok((name.to_string(), value))
+        })
+        .collect()
+}
+
+fn borrowed_params(params: &[(String, Value)]) -> Vec<(&str, &dyn ToSql)> {
+    params
+        .iter()
+        .map(|(name, value)| (name.as_str(), value as &dyn ToSql))
+        .collect()
+}
+
+fn panicked() -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
+        ffi::Error::new(ffi::SQLITE_ERROR),
+        Some("function called on connection thread panicked".to_string()),
+    )
+}
+
+fn terminated() -> rusqlite::Error {
+    rusqlite::Error::SqliteFailure(
+        ffi::Error::new(ffi::SQLITE_MISUSE),
+        Some("connection thread terminated".to_string()),
+    )
+}
+
+struct Shared<T> {
+    value: Option<T>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+struct Sender<T>(Arc<Mutex<Shared<T>>>);
+
+struct Receiver<T>(Arc<Mutex<Shared<T>>>);
+
+fn oneshot<T>() -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Mutex::new(Shared {
+        value: None,
+        closed: false,
+        waker: None,
+    }));
+    (Sender(shared.clone()), Receiver(shared))
+}
+
+impl<T> Sender<T> {
+    fn send(self, value: T) {
+        self.0.lock().unwrap().value = Some(value);
+    }
+}
+
+// Если результат не был отправлен (функция запаниковала), получатель получит None
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut shared = match self.0.lock() {
+            Ok(shared) => shared,
+            Err(e) => e.into_inner(),
+        };
+        shared.closed = true;
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<T>> {
+        let mut shared = self.0.lock().unwrap();
+        if let Some(value) = shared.value.take() {
+            Poll::Ready(Some(value))
+        } else if shared.closed {
+            Poll::Ready(None)
+        } else {
+            shared.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
@@ -0,0 +1,263 @@
+// Функции, скрывающие различия в API поддерживаемых версий rusqlite:
+// feature `rusqlite-0_17` - rusqlite 0.17, feature `rusqlite-latest` - актуальная версия
+use rusqlite::types::ToSql;
+use rusqlite::{Connection, Row, RowIndex, Rows, Statement};
+
+use crate::SqlParam;
+
+#[cfg(feature = "rusqlite-0_17")]
+pub fn execute(stmt: &mut Statement<'_>, params: &[(&str, &dyn ToSql)]) -> rusqlite::Result<usize> {
+    stmt.execute_named(params)
+}
+
+#[cfg(feature = "rusqlite-latest")]
+pub fn execute(stmt: &mut Statement<'_>, params: &[(&str, &dyn ToSql)]) -> rusqlite::Result<usize> {
+    stmt.execute(params)
+}
+
+// Выполнение запроса с параметрами из полей значения: в актуальной версии rusqlite
+// параметры передаются по порядку (SqlParam::to_params), в rusqlite 0.17 - по именам
+#[cfg(feature = "rusqlite-0_17")]
+pub(crate) fn execute_value<P: SqlParam + ?Sized>(
+    stmt: &mut Statement<'_>,
+    value: &P,
+) -> rusqlite::Result<usize> {
+    let params = value.to_named_params(stmt);
+    stmt.execute_named(&params)
+}
+
+#[cfg(feature = "rusqlite-latest")]
+pub(crate) fn execute_value<P: SqlParam + ?Sized>(
+    stmt: &mut Statement<'_>,
+    value: &P,
+) -> rusqlite::Result<usize> {
+    let params = value.to_params(stmt);
+    stmt.execute(params)
+}
+
+#[cfg(feature = "rusqlite-0_17")]
+pub fn query<'a>(
+    stmt: &'a mut Statement<'_>,
+    params: &[(&str, &dyn ToSql)],
+) -> rusqlite::Result<Rows<'a>> {
+    stmt.query_named(params)
+}
+
+#[cfg(feature = "rusqlite-latest")]
+pub fn query<'a>(
+    stmt: &'a mut Statement<'_>,
+    params: &[(&str, &dyn ToSql)],
+) -> rusqlite::Result<Rows<'a>> {
+    stmt.query(params)
+}
+
+#[cfg(feature = "rusqlite-0_17")]
+pub fn query_row<T, F>(
+    conn: &Connection,
+    sql: &str,
+    params: &[(&str, &dyn ToSql)],
+    f: F,
+) -> rusqlite::Result<T>
+where
+    F: FnOnce(&Row<'_>) -> rusqlite::Result<T>,
+{
+    conn.query_row_named(sql, params, f)
+}
+
+#[cfg(feature = "rusqlite-latest")]
+pub fn query_row<T, F>(
+    conn: &Connection,
+    sql: &str,
+    params: &[(&str, &dyn ToSql)],
+    f: F,
+) -> rusqlite::Result<T>
+where
+    F: FnOnce(&Row<'_>) -> rusqlite::Result<T>,
+{
+    conn.query_row(sql, params, f)
+}
+
+#[cfg(feature = "rusqlite-0_17")]
+pub(crate) fn to_value(
+    value: rusqlite::types::ValueRef<'_>,
+) -> rusqlite::Result<rusqlite::types::Value> {
+    Ok(value.into())
+}
+
+#[cfg(feature = "rusqlite-latest")]
+pub(crate) fn to_value(
+    value: rusqlite::types::ValueRef<'_>,
+) -> rusqlite::Result<rusqlite::types::Value> {
+    use std::convert::TryInto;
+    value
+        .try_into()
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+// Значение столбца и его индекс; столбец задаётся именем или индексом. В rusqlite 0.17
+// строка не предоставляет индекс столбца по имени: его запоминает ResolvedIndex
+#[cfg(feature = "rusqlite-0_17")]
+pub(crate) fn column_value<'a, I: RowIndex>(
+    row: &'a Row<'_>,
+    idx: I,
+) -> rusqlite::Result<(usize, rusqlite::types::ValueRef<'a>)> {
+    let resolved = std::cell::Cell::new(0);
+    let value = row.get_raw_checked(ResolvedIndex {
+        column: idx,
+        resolved: &resolved,
+    })?;
+    Ok((resolved.get(), value))
+}
+
+// Индекс столбца, который запоминает найденный по запросу строки индекс
+#[cfg(feature = "rusqlite-0_17")]
+struct ResolvedIndex<'a, I> {
+    column: I,
+    resolved: &'a std::cell::Cell<usize>,
+}
+
+#[cfg(feature = "rusqlite-0_17")]
+impl<I: RowIndex> RowIndex for ResolvedIndex<'_, I> {
+    fn idx(&self, stmt: &Statement<'_>) -> rusqlite::Result<usize> {
+        let idx = self.column.idx(stmt)?;
+        self.resolved.set(idx);
+        Ok(idx)
+    }
+}
+
+#[cfg(feature = "rusqlite-latest")]
+pub(crate) fn column_value<'a, I: RowIndex>(
+    row: &'a Row<'_>,
+    idx: I,
+) -> rusqlite::Result<(usize, rusqlite::types::ValueRef<'a>)> {
+    let idx = idx.idx(row.as_ref())?;
+    row.get_ref(idx).map(|value| (idx, value))
+}
+
+// Первый из столбцов `names`, имеющийся в строке; в rusqlite 0.17 строка не предоставляет
+// имена своих столбцов, поэтому столбец ищется через Row::get_raw_checked без учёта
+// регистра ASCII-символов, независимо от `case_insensitive`
+#[cfg(feature = "rusqlite-0_17")]
+pub(crate) fn row_column<'n>(
+    row: &Row<'_>,
+    names: &[&'n str],
+    _case_insensitive: bool,
+) -> Option<&'n str> {
+    names
+        .iter()
+        .find(|&&name| row.get_raw_checked(name).is_ok())
+        .copied()
+}
+
+#[cfg(feature = "rusqlite-latest")]
+pub(crate) fn row_column(row: &Row<'_>, names: &[&str], case_insensitive: bool) -> Option<usize> {
+    crate::convert::find_column(&row.as_ref().column_names(), names, case_insensitive)
+}
+
+// Вызов f с запросом, которому принадлежит строка. В rusqlite 0.17 строка не предоставляет
+// свой запрос, но передаёт его в RowIndex::idx: запрос получает StatementIndex
+#[cfg(feature = "rusqlite-0_17")]
+pub(crate) fn with_row_statement<R, F>(row: &Row<'_>, f: F) -> rusqlite::Result<R>
+where
+    F: FnOnce(&Statement<'_>) -> rusqlite::Result<R>,
+{
+    let f = std::cell::Cell::new(Some(f));
+    let result = std::cell::Cell::new(None);
+    let _ = row.get_raw_checked(StatementIndex {
+        f: &f,
+        result: &result,
+    });
+    result
+        .into_inner()
+        .unwrap_or(Err(rusqlite::Error::InvalidQuery))
+}
+
+#[cfg(feature = "rusqlite-0_17")]
+struct StatementIndex<'a, F, R> {
+    f: &'a std::cell::Cell<Option<F>>,
+    result: &'a std::cell::Cell<Option<rusqlite::Result<R>>>,
+}
+
+#[cfg(feature = "rusqlite-0_17")]
+impl<F, R> RowIndex for StatementIndex<'_, F, R>
+where
+    F: FnOnce(&Statement<'_>) -> rusqlite::Result<R>,
+{
+    fn idx(&self, stmt: &Statement<'_>) -> rusqlite::Result<usize> {
+        if let Some(f) = self.f.take() {
+            self.result.set(Some(f(stmt)));
+        }
+        // значение столбца не читается
+        Err(rusqlite::Error::InvalidQuery)
+    }
+}
+
+#[cfg(feature = "rusqlite-latest")]
+pub(crate) fn with_row_statement<R, F>(row: &Row<'_>, f: F) -> rusqlite::Result<R>
+where
+    F: FnOnce(&Statement<'_>) -> rusqlite::Result<R>,
+{
+    f(row.as_ref())
+}
+
+// Ошибка преобразования значения столбца, такая же, как у Row::get
+#[cfg(feature = "rusqlite-0_17")]
+pub(crate) fn from_sql_error(
+    _row: &Row<'_>,
+    idx: usize,
+    value: rusqlite::types::ValueRef<'_>,
+    err: rusqlite::types::FromSqlError,
+) -> rusqlite::Error {
+    conversion_error(idx, "", value, err)
+}
+
+// Ошибка преобразования значения столбца с индексом idx и именем name
+#[cfg(feature = "rusqlite-0_17")]
+pub(crate) fn conversion_error(
+    idx: usize,
+    _name: &str,
+    value: rusqlite::types::ValueRef<'_>,
+    err: rusqlite::types::FromSqlError,
+) -> rusqlite::Error {
+    use rusqlite::types::FromSqlError;
+    match err {
+        FromSqlError::InvalidType => rusqlite::Error::InvalidColumnType(idx, value.data_type()),
+        FromSqlError::OutOfRange(i) => rusqlite::Error::IntegralValueOutOfRange(idx, i),
+        FromSqlError::Other(err) => {
+            rusqlite::Error::FromSqlConversionFailure(idx, value.data_type(), err)
+        }
+        #[allow(unreachable_patterns)]
+        err => rusqlite::Error::FromSqlConversionFailure(idx, value.data_type(), Box::new(err)),
+    }
+}
+
+#[cfg(feature = "rusqlite-latest")]
+pub(crate) fn from_sql_error(
+    row: &Row<'_>,
+    idx: usize,
+    value: rusqlite::types::ValueRef<'_>,
+    err: rusqlite::types::FromSqlError,
+) -> rusqlite::Error {
+    let name = row.as_ref().column_name(idx).unwrap_or_default();
+    conversion_error(idx, name, value, err)
+}
+
+#[cfg(feature = "rusqlite-latest")]
+pub(crate) fn conversion_error(
+    idx: usize,
+    name: &str,
+    value: rusqlite::types::ValueRef<'_>,
+    err: rusqlite::types::FromSqlError,
+) -> rusqlite::Error {
+    use rusqlite::types::FromSqlError;
+    match err {
+        FromSqlError::InvalidType => {
+            rusqlite::Error::InvalidColumnType(idx, name.to_string(), value.data_type())
+        }
+        FromSqlError::OutOfRange(i) => rusqlite::Error::IntegralValueOutOfRange(idx, i),
+        FromSqlError::Other(err) => {
+            rusqlite::Error::FromSqlConversionFailure(idx, value.data_type(), err)
+        }
+        err => rusqlite::Error::FromSqlConversionFailure(idx, value.data_type(), Box::new(err)),
+    }
+}
@@ -0,0 +1,221 @@
+// Поддержка атрибутов полей with, from_sql и to_sql: значение поля преобразуется
+// заданными функциями вместо FromSql и ToSql типа поля
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+
+use ref_cast::RefCast;
+use rusqlite::types::{FromSqlResult, ToSql, ToSqlOutput, Type, Value, ValueRef};
+use rusqlite::{Row, RowIndex, Statement};
+
+use crate::{backend, dynamic, SqlParam, SqlResult};
+
+// Функция преобразования значения типа T в параметр запроса
+pub trait ToSqlFn<T> {
+    fn to_sql(value: &T) -> rusqlite::Result<ToSqlOutput<'_>>;
+}
+
+// Значение поля, которое передаётся в запрос через функцию F::to_sql. SqlParam возвращает
+// ссылки на значения полей, поэтому обёртка получается из ссылки на поле (RefCast)
+#[derive(RefCast)]
+#[repr(transparent)]
+pub struct ToSqlWith<F, T> {
+    f: PhantomData<F>,
+    value: T,
+}
+
+impl<F: ToSqlFn<T>, T> ToSqlWith<F, T> {
+    pub fn new(value: &T) -> &Self {
+        Self::ref_cast(value)
+    }
+}
+
+impl<F: ToSqlFn<T>, T> ToSql for ToSqlWith<F, T> {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        F::to_sql(&self.value)
+    }
+}
+
+// Значение столбца (имя или индекс), преобразованное функцией from_sql;
+// ошибки те же, что у Row::get
+pub fn get_with<T, I, F>(row: &Row<'_>, column: I, from_sql: F) -> rusqlite::Result<T>
+where
+    I: RowIndex,
+    F: FnOnce(ValueRef<'_>) -> FromSqlResult<T>,
+{
+    let (idx, value) = backend::column_value(row, column)?;
+    from_sql(value).map_err(|err| backend::from_sql_error(row, idx, value, err))
+}
+
+// Содержит ли столбец значение NULL; используется атрибутом null_as_default
+pub fn is_null<I: RowIndex>(row: &Row<'_>, column: I) -> rusqlite::Result<bool> {
+    backend::column_value(row, column).map(|(_, value)| matches!(value, ValueRef::Null))
+}
+
+// Индекс столбца поля с номером `field` из SqlResult::column_indices;
+// если столбца нет в результатах запроса - ошибка InvalidColumnName, как у Row::get
+pub fn column_at(indices: &[Option<usize>], field: usize, name: &str) -> rusqlite::Result<usize> {
+    match indices.get(field) {
+        Some(&Some(idx)) => Ok(idx),
+        _ => Err(rusqlite::Error::InvalidColumnName(name.to_string())),
+    }
+}
+
+// Индекс первого из столбцов `names`, имеющегося среди столбцов результата `columns`.
+// Имена сравниваются без учёта регистра ASCII-символов, как в rusqlite, или, если задано
+// `case_insensitive`, без учёта регистра любых символов
+pub fn find_column(columns: &[&str], names: &[&str], case_insensitive: bool) -> Option<usize> {
+    names.iter().find_map(|name| {
+        if case_insensitive {
+            let name = name.to_lowercase();
+            columns
+                .iter()
+                .position(|column| column.to_lowercase() == name)
+        } else {
+            columns
+                .iter()
+                .position(|column| column.eq_ignore_ascii_case(name))
+        }
+    })
+}
+
+// Столбец строки для поля с именем столбца names[0] и синонимами names[1..];
+// если столбца нет - ошибка InvalidColumnName, как у Row::get
+pub fn row_column<'n>(
+    row: &Row<'_>,
+    names: &[&'n str],
+    case_insensitive: bool,
+) -> rusqlite::Result<impl RowIndex + Copy + 'n> {
+    backend::row_column(row, names, case_insensitive)
+        .ok_or_else(|| rusqlite::Error::InvalidColumnName(names[0].to_string()))
+}
+
+// Столбцы результата запроса, которым не соответствует ни одно поле структуры
+// (атрибут deny_unknown_columns)
+#[derive(Debug)]
+pub struct UnknownColumns {
+    pub columns: Vec<String>,
+}
+
+impl fmt::Display for UnknownColumns {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown columns in query result: {}",
+            self.columns.join(", ")
+        )
+    }
+}
+
+impl Error for UnknownColumns {}
+
+// Проверка, что каждому столбцу результата `columns` соответствует одно из полей `fields`
+// (имя столбца поля и его синонимы); иначе - ошибка FromSqlConversionFailure с индексом
+// первого неизвестного столбца и UnknownColumns
+pub fn deny_unknown_columns(
+    columns: &[&str],
+    fields: &[&[&str]],
+    case_insensitive: bool,
+) -> rusqlite::Result<()> {
+    let unknown: Vec<usize> = (0..columns.len())
+        .filter(|&i| {
+            !fields
+                .iter()
+                .any(|names| find_column(&columns[i..=i], names, case_insensitive).is_some())
+        })
+        .collect();
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    Err(rusqlite::Error::FromSqlConversionFailure(
+        unknown[0],
+        Type::Null,
+        Box::new(UnknownColumns {
+            columns: unknown.iter().map(|&i| columns[i].to_string()).collect(),
+        }),
+    ))
+}
+
+// SqlResult::check_columns для запроса, которому принадлежит строка
+pub fn check_row_columns<T: SqlResult>(row: &Row<'_>) -> rusqlite::Result<()> {
+    backend::with_row_statement(row, T::check_columns)
+}
+
+// Значения столбцов строки, не соответствующих полям структуры T, для поля с атрибутом rest;
+// `indices` - результат T::column_indices, если он уже вычислен для запроса
+pub fn rest_columns<T, M>(row: &Row<'_>, indices: Option<&[Option<usize>]>) -> rusqlite::Result<M>
+where
+    T: SqlResult,
+    M: FromIterator<(String, Value)>,
+{
+    let columns = backend::with_row_statement(row, |stmt| {
+        let computed;
+        let indices = match indices {
+            Some(indices) => indices,
+            None => {
+                computed = T::column_indices(stmt);
+                &computed
+            }
+        };
+        Ok(stmt
+            .column_names()
+            .into_iter()
+            .enumerate()
+            .filter(|&(i, _)| !indices.contains(&Some(i)))
+            .map(|(i, name)| (i, name.to_string()))
+            .collect::<Vec<_>>())
+    })?;
+    columns
+        .into_iter()
+        .map(|(i, name)| {
+            let (_, value) = backend::column_value(row, i)?;
+            Ok((name, backend::to_value(value)?))
+        })
+        .collect()
+}
+
+// Атрибут expand: параметр `name` (с префиксом `:`) в SQL-коде заменяется списком
+// `name_0, name_1, ...` из len элементов; пустой коллекции соответствует `IN ()`
+pub fn expand_parameter<'s>(sql: Cow<'s, str>, name: &str, len: usize) -> Cow<'s, str> {
+    let names: Vec<String> = (0..len).map(|i| format!("{}_{}", name, i)).collect();
+    let expanded = dynamic::replace_parameter(&sql, name, &names.join(", "));
+    if expanded == sql {
+        sql
+    } else {
+        Cow::Owned(expanded)
+    }
+}
+
+pub fn expanded_params<'a, T: ToSql>(name: &str, values: &'a [T]) -> Vec<(String, &'a dyn ToSql)> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| (format!("{}_{}", name, i), value as &dyn ToSql))
+        .collect()
+}
+
+// Параметры запроса stmt из значения SqlParam, включая элементы параметров-коллекций
+// (expanded - результат value.expanded_params())
+pub fn named_params<'p, P: SqlParam + ?Sized>(
+    stmt: &Statement<'_>,
+    value: &'p P,
+    expanded: &'p [(String, &'p dyn ToSql)],
+) -> Vec<(&'p str, &'p dyn ToSql)> {
+    let mut params = value.to_named_params(stmt);
+    params.extend(expanded_named_params(stmt, expanded));
+    params
+}
+
+// Элементы параметров-коллекций, которые есть в запросе stmt
+pub fn expanded_named_params<'p>(
+    stmt: &Statement<'_>,
+    expanded: &'p [(String, &'p dyn ToSql)],
+) -> Vec<(&'p str, &'p dyn ToSql)> {
+    expanded
+        .iter()
+        .filter(|(name, _)| matches!(stmt.parameter_index(name), Ok(Some(_))))
+        .map(|(name, value)| (name.as_str(), *value))
+        .collect()
+}
@@ -0,0 +1,135 @@
+// Строка результата запроса без структуры: столбцы в порядке их следования в запросе
+use std::iter::FromIterator;
+
+use rusqlite::types::{FromSql, Value, ValueRef};
+use rusqlite::Row;
+
+use crate::{backend, convert, SqlResult};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DynRow {
+    columns: Vec<String>,
+    values: Vec<Value>,
+}
+
+impl DynRow {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.columns
+            .iter()
+            .map(String::as_str)
+            .zip(self.values.iter())
+    }
+
+    // Имена сравниваются без учёта регистра ASCII, как в Row::get;
+    // если столбцов с одинаковым именем несколько, используется первый
+    pub fn index(&self, column: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|c| c.eq_ignore_ascii_case(column))
+    }
+
+    pub fn value(&self, column: &str) -> Option<&Value> {
+        self.index(column).map(|i| &self.values[i])
+    }
+
+    pub fn value_at(&self, idx: usize) -> Option<&Value> {
+        self.values.get(idx)
+    }
+
+    // Значение столбца, преобразованное как в Row::get
+    pub fn get<T: FromSql>(&self, column: &str) -> rusqlite::Result<T> {
+        match self.index(column) {
+            Some(idx) => self.get_at(idx),
+            None => Err(rusqlite::Error::InvalidColumnName(column.to_string())),
+        }
+    }
+
+    pub fn get_at<T: FromSql>(&self, idx: usize) -> rusqlite::Result<T> {
+        let value = match self.values.get(idx) {
+            Some(value) => ValueRef::from(value),
+            None => return Err(rusqlite::Error::InvalidColumnIndex(idx)),
+        };
+        T::column_result(value)
+            .map_err(|err| backend::conversion_error(idx, &self.columns[idx], value, err))
+    }
+
+    pub fn push<S: Into<String>>(&mut self, column: S, value: Value) {
+        self.columns.push(column.into());
+        self.values.push(value);
+    }
+}
+
+impl FromIterator<(String, Value)> for DynRow {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(iter: I) -> Self {
+        let mut row = DynRow::new();
+        for (column, value) in iter {
+            row.push(column, value);
+        }
+        row
+    }
+}
+
+impl<'a> IntoIterator for &'a DynRow {
+    type Item = (&'a str, &'a Value);
+    type IntoIter = Box<dyn Iterator<Item = (&'a str, &'a Value)> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
+
+// Все столбцы строки
+impl SqlResult for DynRow {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
+        convert::rest_columns::<Self, _>(row, Some(&[]))
+    }
+}
+
+// Сериализуется как map "столбец - значение": NULL - unit (null в JSON), BLOB - bytes
+#[cfg(feature = "serde")]
+impl serde::Serialize for DynRow {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+        for (column, value) in self.iter() {
+            map.serialize_entry(column, &SerializeValue(value))?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+struct SerializeValue<'a>(&'a Value);
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for SerializeValue<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self.0 {
+            Value::Null => serializer.serialize_unit(),
+            Value::Integer(i) => serializer.serialize_i64(i),
+            Value::Real(f) => serializer.serialize_f64(f),
+            Value::Text(ref s) => serializer.serialize_str(s),
+            Value::Blob(ref b) => serializer.serialize_bytes(b),
+        }
+    }
+}
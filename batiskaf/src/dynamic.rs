@@ -0,0 +1,159 @@
+use rusqlite::types::ToSql;
+use rusqlite::Statement;
+
+use crate::SqlParam;
+
+// Динамический SQL-запрос, собираемый из фрагментов (аналог <if>, <where>, <set>, <foreach> в MyBatis)
+#[derive(Default)]
+pub struct DynamicSql<'a> {
+    parts: Vec<String>,
+    params: Vec<(String, &'a dyn ToSql)>,
+}
+
+impl<'a> DynamicSql<'a> {
+    pub fn new(sql: &str) -> Self {
+        let mut x = DynamicSql::default();
+        x.push(sql);
+        x
+    }
+
+    pub fn sql(&self) -> String {
+        self.parts.join(" ")
+    }
+
+    pub fn params(&self) -> Vec<(&str, &dyn ToSql)> {
+        self.params.iter().map(|(n, v)| (n.as_str(), *v)).collect()
+    }
+
+    pub fn push(&mut self, sql: &str) -> &mut Self {
+        let sql = sql.trim();
+        if !sql.is_empty() {
+            self.parts.push(sql.to_string());
+        }
+        self
+    }
+
+    pub fn push_if(&mut self, condition: bool, sql: &str) -> &mut Self {
+        if condition {
+            self.push(sql);
+        }
+        self
+    }
+
+    // Фрагмент добавляется, только если значение параметра не None
+    pub fn push_some<T: ToSql>(
+        &mut self,
+        sql: &str,
+        name: &str,
+        value: &'a Option<T>,
+    ) -> &mut Self {
+        if let Some(ref value) = *value {
+            self.push(sql);
+            self.bind(name, value);
+        }
+        self
+    }
+
+    // Параметр `name` во фрагменте заменяется списком `name_0, name_1, ...`
+    pub fn push_in<T: ToSql>(&mut self, sql: &str, name: &str, values: &'a [T]) -> &mut Self {
+        let name = parameter_name(name);
+        let names: Vec<String> = (0..values.len())
+            .map(|i| format!("{}_{}", name, i))
+            .collect();
+        self.push(&replace_parameter(sql, &name, &names.join(", ")));
+        for (n, v) in names.into_iter().zip(values) {
+            self.params.push((n, v as &dyn ToSql));
+        }
+        self
+    }
+
+    pub fn bind(&mut self, name: &str, value: &'a dyn ToSql) -> &mut Self {
+        self.params.push((parameter_name(name), value));
+        self
+    }
+
+    pub fn where_clause<F: FnOnce(&mut DynamicSql<'a>)>(&mut self, f: F) -> &mut Self {
+        self.trim("WHERE ", " AND ", "", f)
+    }
+
+    pub fn set_clause<F: FnOnce(&mut DynamicSql<'a>)>(&mut self, f: F) -> &mut Self {
+        self.trim("SET ", ", ", "", f)
+    }
+
+    pub fn or<F: FnOnce(&mut DynamicSql<'a>)>(&mut self, f: F) -> &mut Self {
+        self.trim("(", " OR ", ")", f)
+    }
+
+    // Фрагменты, добавленные в `f`, объединяются через `separator`;
+    // если ни одного фрагмента не добавлено, не добавляются и `prefix` с `suffix`
+    pub fn trim<F: FnOnce(&mut DynamicSql<'a>)>(
+        &mut self,
+        prefix: &str,
+        separator: &str,
+        suffix: &str,
+        f: F,
+    ) -> &mut Self {
+        let mut inner = DynamicSql::default();
+        f(&mut inner);
+        if !inner.parts.is_empty() {
+            let sql = inner.parts.join(separator);
+            self.push(&format!("{}{}{}", prefix, sql, suffix));
+            self.params.extend(inner.params);
+        }
+        self
+    }
+}
+
+impl<'a> SqlParam for DynamicSql<'a> {
+    fn to_named_params(&self, stmt: &Statement<'_>) -> Vec<(&str, &dyn ToSql)> {
+        self.params()
+            .into_iter()
+            .filter(|(name, _)| matches!(stmt.parameter_index(name), Ok(Some(_))))
+            .collect()
+    }
+}
+
+fn parameter_name(name: &str) -> String {
+    if name.starts_with(':') {
+        name.to_string()
+    } else {
+        format!(":{}", name)
+    }
+}
+
+// Заменяет в SQL-коде параметр `name` (вместе с префиксом `:`) на `replacement`
+pub(crate) fn replace_parameter(sql: &str, name: &str, replacement: &str) -> String {
+    let mut result = String::with_capacity(sql.len());
+    let mut quote = None;
+    let mut rest = sql;
+    while let Some(c) = rest.chars().next() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => (),
+            None if c == '\'' || c == '"' || c == '`' => quote = Some(c),
+            // комментарии копируются без изменений
+            None if rest.starts_with("--") || rest.starts_with("/*") => {
+                let end = if rest.starts_with("--") {
+                    rest.find('\n').map_or(rest.len(), |i| i + 1)
+                } else {
+                    rest[2..].find("*/").map_or(rest.len(), |i| i + 4)
+                };
+                result.push_str(&rest[..end]);
+                rest = &rest[end..];
+                continue;
+            }
+            None if rest.starts_with(name) => {
+                let next = rest[name.len()..].chars().next();
+                if !next.is_some_and(|x| x.is_alphanumeric() || x == '_') {
+                    result.push_str(replacement);
+                    rest = &rest[name.len()..];
+                    continue;
+                }
+            }
+            None => (),
+        }
+        result.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    result
+}
@@ -0,0 +1,58 @@
+// Поддержка атрибута поля json: значение поля хранится в столбце как JSON-текст
+use std::error::Error;
+use std::fmt;
+
+use rusqlite::types::{FromSqlError, FromSqlResult, ToSqlOutput, Value, ValueRef};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+// Ошибка разбора JSON-значения столбца
+#[derive(Debug)]
+pub struct JsonError {
+    pub column: String,
+    pub source: serde_json::Error,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid JSON in column `{}`: {}",
+            self.column, self.source
+        )
+    }
+}
+
+impl Error for JsonError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+// JSON null сохраняется как NULL, поэтому Option<T> отображается на NULL-столбец
+pub fn to_sql<T: Serialize>(value: &T) -> rusqlite::Result<ToSqlOutput<'_>> {
+    let json = serde_json::to_string(value)
+        .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?;
+    if json == "null" {
+        Ok(ToSqlOutput::Owned(Value::Null))
+    } else {
+        Ok(ToSqlOutput::Owned(Value::Text(json)))
+    }
+}
+
+pub fn from_sql<T: DeserializeOwned>(column: &str, value: ValueRef<'_>) -> FromSqlResult<T> {
+    let json = match value {
+        ValueRef::Null => &b"null"[..],
+        // в rusqlite 0.17 ValueRef::Text содержит &str, в актуальной версии - &[u8]
+        #[allow(clippy::useless_asref)]
+        ValueRef::Text(s) => s.as_ref(),
+        ValueRef::Blob(b) => b,
+        _ => return Err(FromSqlError::InvalidType),
+    };
+    serde_json::from_slice(json).map_err(|source| {
+        FromSqlError::Other(Box::new(JsonError {
+            column: column.to_string(),
+            source,
+        }))
+    })
+}
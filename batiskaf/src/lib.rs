@@ -1,5 +1,18 @@
+#[cfg(all(feature = "rusqlite-0_17", feature = "rusqlite-latest"))]
+compile_error!("features `rusqlite-0_17` and `rusqlite-latest` are mutually exclusive");
+#[cfg(not(any(feature = "rusqlite-0_17", feature = "rusqlite-latest")))]
+compile_error!("one of features `rusqlite-0_17`, `rusqlite-latest` must be enabled");
+
+pub use rusqlite;
+
+use std::borrow::Cow;
+
+#[cfg(feature = "rusqlite-latest")]
+use rusqlite::types::Null;
 use rusqlite::types::ToSql;
-use rusqlite::{self, Connection, Row, Statement};
+#[cfg(feature = "rusqlite-latest")]
+use rusqlite::{params_from_iter, ParamsFromIter};
+use rusqlite::{Connection, Row, Statement};
 
 // Реэкспорт derive-макросов из batiskaf_derive
 #[cfg(feature = "batiskaf_derive")]
@@ -10,15 +23,95 @@ extern crate batiskaf_derive;
 #[doc(hidden)]
 pub use batiskaf_derive::*;
 
-#[cfg(test)]
+mod association;
+#[cfg(feature = "async")]
+mod async_connection;
+pub mod backend;
+pub mod convert;
+mod dyn_row;
+mod dynamic;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod migrate;
+mod params;
+#[cfg(feature = "pool")]
+pub mod pool;
+mod schema;
+mod select;
+#[cfg(any(
+    feature = "chrono",
+    feature = "time",
+    feature = "uuid",
+    feature = "rust_decimal"
+))]
+pub mod storage;
+
+pub use crate::association::HasMany;
+#[cfg(feature = "async")]
+pub use crate::async_connection::AsyncConnection;
+pub use crate::dyn_row::DynRow;
+pub use crate::dynamic::DynamicSql;
+pub use crate::params::NamedParams;
+pub use crate::schema::{ColumnDef, MappingDiff, MappingReport};
+pub use crate::select::{select_iter, SelectIter};
+
+// Юнит-тесты используют API актуальной версии rusqlite;
+// rusqlite 0.17 проверяется тестами в tests/rusqlite-0_17
+#[cfg(all(test, feature = "rusqlite-latest"))]
 mod tests;
 
 pub trait SqlParam {
-    fn to_named_params(&self, stmt: &Statement) -> Vec<(&str, &dyn ToSql)>;
+    fn to_named_params(&self, stmt: &Statement<'_>) -> Vec<(&str, &dyn ToSql)>;
+
+    // SQL-код запроса, в котором параметры-коллекции (атрибут expand) `:name`
+    // заменены списками `:name_0, :name_1, ...`; вызывается до подготовки запроса
+    fn expand_sql<'s>(&self, sql: Cow<'s, str>) -> Cow<'s, str> {
+        sql
+    }
+
+    // Значения элементов параметров-коллекций с именами `:name_0, :name_1, ...`
+    fn expanded_params(&self) -> Vec<(String, &dyn ToSql)> {
+        Vec::new()
+    }
+
+    // Параметры в порядке их следования в запросе; результат можно передать
+    // в любой метод rusqlite, принимающий `impl Params`.
+    // Параметры запроса, для которых нет значения, получают значение NULL
+    #[cfg(feature = "rusqlite-latest")]
+    fn to_params(&self, stmt: &Statement<'_>) -> ParamsFromIter<Vec<&dyn ToSql>> {
+        let named = self.to_named_params(stmt);
+        let expanded = self.expanded_params();
+        let params = (1..=stmt.parameter_count())
+            .map(|i| {
+                stmt.parameter_name(i)
+                    .and_then(|name| {
+                        let named = named.iter().find(|&&(n, _)| n == name).map(|&(_, v)| v);
+                        named.or_else(|| expanded.iter().find(|(n, _)| n == name).map(|&(_, v)| v))
+                    })
+                    .unwrap_or(&Null as &dyn ToSql)
+            })
+            .collect();
+        params_from_iter(params)
+    }
 }
 
 pub trait SqlResult: Sized {
-    fn from_row(row: &Row) -> rusqlite::Result<Self>;
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self>;
+
+    // Индексы столбцов, соответствующих полям, в результатах запроса stmt;
+    // вычисляются один раз для запроса и передаются в from_row_indexed
+    fn column_indices(_stmt: &Statement<'_>) -> Vec<Option<usize>> {
+        Vec::new()
+    }
+
+    fn from_row_indexed(row: &Row<'_>, _indices: &[Option<usize>]) -> rusqlite::Result<Self> {
+        Self::from_row(row)
+    }
+
+    // Проверка столбцов результата запроса stmt до чтения строк (deny_unknown_columns)
+    fn check_columns(_stmt: &Statement<'_>) -> rusqlite::Result<()> {
+        Ok(())
+    }
 }
 
 pub trait SqlInsert {
@@ -33,6 +126,11 @@ pub trait SqlDelete {
     fn delete_statement(table: &str) -> String;
 }
 
+pub trait SqlSchema {
+    fn create_table_statement(table: &str) -> String;
+    fn columns() -> Vec<ColumnDef>;
+}
+
 pub trait BatiskafConnection {
     fn select_one<T: SqlResult>(
         &self,
@@ -44,9 +142,26 @@ pub trait BatiskafConnection {
         sql: &str,
         params: &[(&str, &dyn ToSql)],
     ) -> rusqlite::Result<Vec<T>>;
+    fn select_one_with<T: SqlResult, P: SqlParam + ?Sized>(
+        &self,
+        sql: &str,
+        params: &P,
+    ) -> rusqlite::Result<T>;
+    fn select_many_with<T: SqlResult, P: SqlParam + ?Sized>(
+        &self,
+        sql: &str,
+        params: &P,
+    ) -> rusqlite::Result<Vec<T>>;
+    fn execute_with<P: SqlParam + ?Sized>(&self, sql: &str, params: &P) -> rusqlite::Result<usize>;
     fn insert<T: SqlInsert + SqlParam>(&self, table: &str, value: &T) -> rusqlite::Result<i64>;
     fn update<T: SqlUpdate + SqlParam>(&self, table: &str, value: &T) -> rusqlite::Result<usize>;
     fn delete<T: SqlDelete + SqlParam>(&self, table: &str, value: &T) -> rusqlite::Result<usize>;
+    fn verify_mapping<T: SqlSchema>(&self, table: &str) -> rusqlite::Result<MappingReport>;
+    fn load_has_many<C: SqlResult + Clone, P: HasMany<C>>(
+        &self,
+        parents: &mut [P],
+        table: &str,
+    ) -> rusqlite::Result<()>;
 }
 
 impl BatiskafConnection for Connection {
@@ -55,7 +170,9 @@ impl BatiskafConnection for Connection {
         sql: &str,
         params: &[(&str, &dyn ToSql)],
     ) -> rusqlite::Result<T> {
-        self.query_row_named(sql, params, T::from_row)
+        let mut stmt = self.prepare(sql)?;
+        let row = select_iter(&mut stmt, params)?.next();
+        row.unwrap_or(Err(rusqlite::Error::QueryReturnedNoRows))
     }
 
     fn select_many<T: SqlResult>(
@@ -64,18 +181,45 @@ impl BatiskafConnection for Connection {
         params: &[(&str, &dyn ToSql)],
     ) -> rusqlite::Result<Vec<T>> {
         let mut stmt = self.prepare(sql)?;
-        let mut rows = stmt.query_named(params)?;
-        let mut result = Vec::new();
-        while let Some(row) = rows.next()? {
-            result.push(T::from_row(&row)?);
-        }
-        Ok(result)
+        let result = select_iter(&mut stmt, params)?.collect();
+        result
+    }
+
+    fn select_one_with<T: SqlResult, P: SqlParam + ?Sized>(
+        &self,
+        sql: &str,
+        params: &P,
+    ) -> rusqlite::Result<T> {
+        let mut stmt = self.prepare(&params.expand_sql(Cow::Borrowed(sql)))?;
+        let expanded = params.expanded_params();
+        let params = convert::named_params(&stmt, params, &expanded);
+        let row = select_iter(&mut stmt, &params)?.next();
+        row.unwrap_or(Err(rusqlite::Error::QueryReturnedNoRows))
+    }
+
+    fn select_many_with<T: SqlResult, P: SqlParam + ?Sized>(
+        &self,
+        sql: &str,
+        params: &P,
+    ) -> rusqlite::Result<Vec<T>> {
+        let mut stmt = self.prepare(&params.expand_sql(Cow::Borrowed(sql)))?;
+        let expanded = params.expanded_params();
+        let params = convert::named_params(&stmt, params, &expanded);
+        let result = select_iter(&mut stmt, &params)?.collect();
+        result
+    }
+
+    fn execute_with<P: SqlParam + ?Sized>(&self, sql: &str, params: &P) -> rusqlite::Result<usize> {
+        let mut stmt = self.prepare(&params.expand_sql(Cow::Borrowed(sql)))?;
+        let expanded = params.expanded_params();
+        let params = convert::named_params(&stmt, params, &expanded);
+        backend::execute(&mut stmt, &params)
     }
 
     fn insert<T: SqlInsert + SqlParam>(&self, table: &str, value: &T) -> rusqlite::Result<i64> {
         let sql = T::insert_statement(table);
         let mut stmt = self.prepare(&sql)?;
-        let changes = stmt.execute_named(&value.to_named_params(&stmt))?;
+        let changes = backend::execute_value(&mut stmt, value)?;
         match changes {
             1 => Ok(self.last_insert_rowid()),
             _ => Err(rusqlite::Error::StatementChangedRows(changes)),
@@ -85,12 +229,24 @@ impl BatiskafConnection for Connection {
     fn update<T: SqlUpdate + SqlParam>(&self, table: &str, value: &T) -> rusqlite::Result<usize> {
         let sql = T::update_statement(table);
         let mut stmt = self.prepare(&sql)?;
-        stmt.execute_named(&value.to_named_params(&stmt))
+        backend::execute_value(&mut stmt, value)
     }
 
     fn delete<T: SqlDelete + SqlParam>(&self, table: &str, value: &T) -> rusqlite::Result<usize> {
         let sql = T::delete_statement(table);
         let mut stmt = self.prepare(&sql)?;
-        stmt.execute_named(&value.to_named_params(&stmt))
+        backend::execute_value(&mut stmt, value)
+    }
+
+    fn verify_mapping<T: SqlSchema>(&self, table: &str) -> rusqlite::Result<MappingReport> {
+        schema::verify_mapping::<T>(self, table)
+    }
+
+    fn load_has_many<C: SqlResult + Clone, P: HasMany<C>>(
+        &self,
+        parents: &mut [P],
+        table: &str,
+    ) -> rusqlite::Result<()> {
+        association::load_has_many(self, parents, table)
     }
 }
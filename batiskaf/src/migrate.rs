@@ -0,0 +1,227 @@
+use std::error;
+use std::fmt;
+
+use rusqlite::types::ToSql;
+use rusqlite::Connection;
+
+use crate::backend;
+
+// Таблица, в которой хранятся применённые миграции
+pub const MIGRATIONS_TABLE: &str = "__batiskaf_migrations";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Tracker {
+    // применённые миграции и их контрольные суммы хранятся в таблице __batiskaf_migrations
+    Table,
+    // количество применённых миграций хранится в PRAGMA user_version
+    UserVersion,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Sqlite(rusqlite::Error),
+    // применённая миграция была изменена
+    ChecksumMismatch {
+        version: usize,
+        name: String,
+        expected: String,
+        actual: String,
+    },
+    // применённая миграция отсутствует в списке или находится на другом месте
+    UnknownMigration {
+        version: usize,
+        name: String,
+    },
+    // в БД применено больше миграций, чем есть в списке
+    VersionTooNew {
+        current: usize,
+        latest: usize,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Sqlite(ref e) => e.fmt(f),
+            Error::ChecksumMismatch {
+                version,
+                ref name,
+                ref expected,
+                ref actual,
+            } => write!(
+                f,
+                "migration {} `{}` was modified after it was applied (checksum {}, expected {})",
+                version, name, actual, expected
+            ),
+            Error::UnknownMigration { version, ref name } => write!(
+                f,
+                "applied migration {} `{}` not found in migration list",
+                version, name
+            ),
+            Error::VersionTooNew { current, latest } => write!(
+                f,
+                "database version {} is newer than latest migration {}",
+                current, latest
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Sqlite(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::Sqlite(e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// Упорядоченный список миграций (название, SQL-код)
+pub struct Migrator<'a> {
+    migrations: &'a [(&'a str, &'a str)],
+    tracker: Tracker,
+}
+
+impl<'a> Migrator<'a> {
+    pub fn new(migrations: &'a [(&'a str, &'a str)]) -> Self {
+        Migrator {
+            migrations,
+            tracker: Tracker::Table,
+        }
+    }
+
+    pub fn tracker(mut self, tracker: Tracker) -> Self {
+        self.tracker = tracker;
+        self
+    }
+
+    // Версия БД - количество применённых миграций
+    pub fn version(&self, conn: &Connection) -> Result<usize> {
+        match self.tracker {
+            Tracker::Table => {
+                create_table(conn)?;
+                self.verify(conn)
+            }
+            Tracker::UserVersion => {
+                let version = user_version(conn)?;
+                if version > self.migrations.len() {
+                    return Err(Error::VersionTooNew {
+                        current: version,
+                        latest: self.migrations.len(),
+                    });
+                }
+                Ok(version)
+            }
+        }
+    }
+
+    // Применяет неприменённые миграции, каждую в отдельной транзакции,
+    // и возвращает их названия
+    pub fn run(&self, conn: &mut Connection) -> Result<Vec<&'a str>> {
+        let version = self.version(conn)?;
+        let mut applied = Vec::new();
+        for (i, &(name, sql)) in self.migrations.iter().enumerate().skip(version) {
+            let version = i + 1;
+            let tx = conn.transaction()?;
+            tx.execute_batch(sql)?;
+            match self.tracker {
+                Tracker::Table => {
+                    let mut stmt = tx.prepare(&format!(
+                        "INSERT INTO {} (version, name, checksum) \
+                         VALUES (:version, :name, :checksum)",
+                        MIGRATIONS_TABLE
+                    ))?;
+                    backend::execute(
+                        &mut stmt,
+                        &[
+                            (":version", &(version as i64) as &dyn ToSql),
+                            (":name", &name),
+                            (":checksum", &checksum(sql)),
+                        ],
+                    )?;
+                }
+                Tracker::UserVersion => {
+                    tx.execute_batch(&format!("PRAGMA user_version = {}", version))?;
+                }
+            }
+            tx.commit()?;
+            applied.push(name);
+        }
+        Ok(applied)
+    }
+
+    fn verify(&self, conn: &Connection) -> Result<usize> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT version, name, checksum FROM {} ORDER BY version",
+            MIGRATIONS_TABLE
+        ))?;
+        let mut rows = backend::query(&mut stmt, &[])?;
+        let mut version = 0;
+        while let Some(row) = rows.next()? {
+            let v = row.get::<_, i64>(0)? as usize;
+            let name: String = row.get(1)?;
+            let expected: String = row.get(2)?;
+            if v != version + 1 {
+                return Err(Error::UnknownMigration { version: v, name });
+            }
+            let (n, sql) = match self.migrations.get(version) {
+                Some(m) => *m,
+                None => {
+                    return Err(Error::VersionTooNew {
+                        current: v,
+                        latest: self.migrations.len(),
+                    });
+                }
+            };
+            if n != name {
+                return Err(Error::UnknownMigration { version: v, name });
+            }
+            let actual = checksum(sql);
+            if actual != expected {
+                return Err(Error::ChecksumMismatch {
+                    version: v,
+                    name,
+                    expected,
+                    actual,
+                });
+            }
+            version = v;
+        }
+        Ok(version)
+    }
+}
+
+fn create_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS {} (\
+            version INTEGER PRIMARY KEY, \
+            name TEXT NOT NULL, \
+            checksum TEXT NOT NULL, \
+            applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP\
+        )",
+        MIGRATIONS_TABLE
+    ))
+}
+
+fn user_version(conn: &Connection) -> rusqlite::Result<usize> {
+    backend::query_row(conn, "PRAGMA user_version", &[], |row| row.get::<_, i64>(0))
+        .map(|v| v as usize)
+}
+
+// FNV-1a, 64 бита
+fn checksum(sql: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in sql.bytes() {
+        hash ^= u64::from(b);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
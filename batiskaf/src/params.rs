@@ -0,0 +1,49 @@
+use std::ops::Deref;
+
+use rusqlite::types::ToSql;
+use rusqlite::Statement;
+
+use crate::SqlParam;
+
+// Набор именованных параметров, создаваемый макросом `params!`
+pub struct NamedParams<'a> {
+    #[doc(hidden)]
+    pub params: &'a [(&'a str, &'a dyn ToSql)],
+}
+
+impl<'a> Deref for NamedParams<'a> {
+    type Target = [(&'a str, &'a dyn ToSql)];
+
+    fn deref(&self) -> &Self::Target {
+        self.params
+    }
+}
+
+impl<'a> SqlParam for NamedParams<'a> {
+    fn to_named_params(&self, stmt: &Statement<'_>) -> Vec<(&str, &dyn ToSql)> {
+        let mut params = Vec::new();
+        for &(name, value) in self.params {
+            if let Ok(Some(_)) = stmt.parameter_index(name) {
+                params.push((name, value));
+            }
+        }
+        params
+    }
+}
+
+#[macro_export]
+macro_rules! params {
+    () => {
+        $crate::NamedParams { params: &[] }
+    };
+    ($($(:)? $name:ident => $value:expr),+ $(,)?) => {
+        $crate::NamedParams {
+            params: &[$(
+                (
+                    concat!(":", stringify!($name)),
+                    &$value as &dyn $crate::rusqlite::types::ToSql,
+                )
+            ),+],
+        }
+    };
+}
@@ -0,0 +1,363 @@
+use std::error;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use rusqlite::types::ToSql;
+use rusqlite::Connection;
+
+use crate::{
+    BatiskafConnection, HasMany, MappingReport, SqlDelete, SqlInsert, SqlParam, SqlResult,
+    SqlSchema, SqlUpdate,
+};
+
+type Connect = dyn Fn() -> rusqlite::Result<Connection> + Send + Sync;
+type Init = dyn Fn(&Connection) -> rusqlite::Result<()> + Send + Sync;
+
+#[derive(Debug)]
+pub enum Error {
+    Sqlite(rusqlite::Error),
+    // за отведённое время не удалось получить соединение
+    Timeout,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Sqlite(ref e) => e.fmt(f),
+            Error::Timeout => write!(f, "timed out waiting for connection"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Sqlite(ref e) => Some(e),
+            Error::Timeout => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::Sqlite(e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+// Открывает соединения и выполняет для каждого нового соединения функции инициализации
+// (PRAGMA, пользовательские функции и т.п.)
+#[derive(Clone)]
+pub struct Connector {
+    connect: Arc<Connect>,
+    init: Vec<Arc<Init>>,
+}
+
+impl Connector {
+    pub fn new<F>(connect: F) -> Self
+    where
+        F: Fn() -> rusqlite::Result<Connection> + Send + Sync + 'static,
+    {
+        Connector {
+            connect: Arc::new(connect),
+            init: Vec::new(),
+        }
+    }
+
+    pub fn on_connect<F>(mut self, init: F) -> Self
+    where
+        F: Fn(&Connection) -> rusqlite::Result<()> + Send + Sync + 'static,
+    {
+        self.init.push(Arc::new(init));
+        self
+    }
+
+    pub fn connect(&self) -> rusqlite::Result<Connection> {
+        let conn = (self.connect)()?;
+        for init in self.init.iter() {
+            init(&conn)?;
+        }
+        Ok(conn)
+    }
+}
+
+pub struct PoolBuilder {
+    connector: Connector,
+    max_size: usize,
+    timeout: Option<Duration>,
+}
+
+impl PoolBuilder {
+    pub fn max_size(mut self, max_size: usize) -> Self {
+        assert!(max_size > 0, "max_size must be positive");
+        self.max_size = max_size;
+        self
+    }
+
+    // Время ожидания свободного соединения; по-умолчанию ожидание не ограничено
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn on_connect<F>(mut self, init: F) -> Self
+    where
+        F: Fn(&Connection) -> rusqlite::Result<()> + Send + Sync + 'static,
+    {
+        self.connector = self.connector.on_connect(init);
+        self
+    }
+
+    pub fn build(self) -> Pool {
+        Pool {
+            inner: Arc::new(Inner {
+                connector: self.connector,
+                max_size: self.max_size,
+                timeout: self.timeout,
+                state: Mutex::new(State {
+                    idle: Vec::new(),
+                    size: 0,
+                }),
+                available: Condvar::new(),
+            }),
+        }
+    }
+}
+
+struct State {
+    idle: Vec<Connection>,
+    size: usize,
+}
+
+struct Inner {
+    connector: Connector,
+    max_size: usize,
+    timeout: Option<Duration>,
+    state: Mutex<State>,
+    available: Condvar,
+}
+
+// Пул соединений; соединения открываются по мере необходимости, но не более max_size
+#[derive(Clone)]
+pub struct Pool {
+    inner: Arc<Inner>,
+}
+
+impl Pool {
+    pub fn builder<F>(connect: F) -> PoolBuilder
+    where
+        F: Fn() -> rusqlite::Result<Connection> + Send + Sync + 'static,
+    {
+        PoolBuilder {
+            connector: Connector::new(connect),
+            max_size: 10,
+            timeout: None,
+        }
+    }
+
+    pub fn get(&self) -> Result<PooledConnection> {
+        let deadline = self.inner.timeout.map(|t| Instant::now() + t);
+        let mut state = self.inner.state.lock().unwrap();
+        loop {
+            if let Some(conn) = state.idle.pop() {
+                return Ok(self.pooled(conn));
+            }
+            if state.size < self.inner.max_size {
+                state.size += 1;
+                drop(state);
+                return match self.inner.connector.connect() {
+                    Ok(conn) => Ok(self.pooled(conn)),
+                    Err(e) => {
+                        self.inner.state.lock().unwrap().size -= 1;
+                        self.inner.available.notify_one();
+                        Err(e.into())
+                    }
+                };
+            }
+            state = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(Error::Timeout);
+                    }
+                    self.inner
+                        .available
+                        .wait_timeout(state, deadline - now)
+                        .unwrap()
+                        .0
+                }
+                None => self.inner.available.wait(state).unwrap(),
+            };
+        }
+    }
+
+    // Количество открытых соединений и количество свободных соединений
+    pub fn state(&self) -> (usize, usize) {
+        let state = self.inner.state.lock().unwrap();
+        (state.size, state.idle.len())
+    }
+
+    fn pooled(&self, conn: Connection) -> PooledConnection {
+        PooledConnection {
+            pool: self.inner.clone(),
+            conn: Some(conn),
+        }
+    }
+}
+
+pub struct PooledConnection {
+    pool: Arc<Inner>,
+    conn: Option<Connection>,
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            let mut state = self.pool.state.lock().unwrap();
+            // соединение с незавершённой транзакцией не возвращается в пул
+            if conn.is_autocommit() {
+                state.idle.push(conn);
+            } else {
+                state.size -= 1;
+            }
+            self.pool.available.notify_one();
+        }
+    }
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn.as_mut().unwrap()
+    }
+}
+
+macro_rules! delegate_batiskaf_connection {
+    () => {
+        fn select_one<T: SqlResult>(
+            &self,
+            sql: &str,
+            params: &[(&str, &dyn ToSql)],
+        ) -> rusqlite::Result<T> {
+            <Connection as BatiskafConnection>::select_one(self, sql, params)
+        }
+
+        fn select_many<T: SqlResult>(
+            &self,
+            sql: &str,
+            params: &[(&str, &dyn ToSql)],
+        ) -> rusqlite::Result<Vec<T>> {
+            <Connection as BatiskafConnection>::select_many(self, sql, params)
+        }
+
+        fn select_one_with<T: SqlResult, P: SqlParam + ?Sized>(
+            &self,
+            sql: &str,
+            params: &P,
+        ) -> rusqlite::Result<T> {
+            <Connection as BatiskafConnection>::select_one_with(self, sql, params)
+        }
+
+        fn select_many_with<T: SqlResult, P: SqlParam + ?Sized>(
+            &self,
+            sql: &str,
+            params: &P,
+        ) -> rusqlite::Result<Vec<T>> {
+            <Connection as BatiskafConnection>::select_many_with(self, sql, params)
+        }
+
+        fn execute_with<P: SqlParam + ?Sized>(
+            &self,
+            sql: &str,
+            params: &P,
+        ) -> rusqlite::Result<usize> {
+            <Connection as BatiskafConnection>::execute_with(self, sql, params)
+        }
+
+        fn insert<T: SqlInsert + SqlParam>(&self, table: &str, value: &T) -> rusqlite::Result<i64> {
+            <Connection as BatiskafConnection>::insert(self, table, value)
+        }
+
+        fn update<T: SqlUpdate + SqlParam>(
+            &self,
+            table: &str,
+            value: &T,
+        ) -> rusqlite::Result<usize> {
+            <Connection as BatiskafConnection>::update(self, table, value)
+        }
+
+        fn delete<T: SqlDelete + SqlParam>(
+            &self,
+            table: &str,
+            value: &T,
+        ) -> rusqlite::Result<usize> {
+            <Connection as BatiskafConnection>::delete(self, table, value)
+        }
+
+        fn verify_mapping<T: SqlSchema>(&self, table: &str) -> rusqlite::Result<MappingReport> {
+            <Connection as BatiskafConnection>::verify_mapping::<T>(self, table)
+        }
+
+        fn load_has_many<C: SqlResult + Clone, P: HasMany<C>>(
+            &self,
+            parents: &mut [P],
+            table: &str,
+        ) -> rusqlite::Result<()> {
+            <Connection as BatiskafConnection>::load_has_many(self, parents, table)
+        }
+    };
+}
+
+impl BatiskafConnection for PooledConnection {
+    delegate_batiskaf_connection!();
+}
+
+// Менеджер соединений для r2d2
+#[cfg(feature = "r2d2")]
+#[derive(Clone)]
+pub struct SqliteConnectionManager {
+    connector: Connector,
+}
+
+#[cfg(feature = "r2d2")]
+impl SqliteConnectionManager {
+    pub fn new(connector: Connector) -> Self {
+        SqliteConnectionManager { connector }
+    }
+}
+
+#[cfg(feature = "r2d2")]
+impl r2d2::ManageConnection for SqliteConnectionManager {
+    type Connection = Connection;
+    type Error = rusqlite::Error;
+
+    fn connect(&self) -> rusqlite::Result<Connection> {
+        self.connector.connect()
+    }
+
+    fn is_valid(&self, conn: &mut Connection) -> rusqlite::Result<()> {
+        conn.execute_batch("SELECT 1")
+    }
+
+    fn has_broken(&self, _conn: &mut Connection) -> bool {
+        false
+    }
+}
+
+#[cfg(feature = "r2d2")]
+impl<M> BatiskafConnection for r2d2::PooledConnection<M>
+where
+    M: r2d2::ManageConnection<Connection = Connection>,
+{
+    delegate_batiskaf_connection!();
+}
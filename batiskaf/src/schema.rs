@@ -0,0 +1,168 @@
+use std::fmt;
+
+use rusqlite::Connection;
+
+use crate::backend;
+use crate::SqlSchema;
+
+// Описание столбца, соответствующего полю структуры
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ColumnDef {
+    pub name: String,
+    pub nullable: bool,
+    pub primary_key: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MappingDiff {
+    // таблица не найдена
+    MissingTable,
+    // для поля структуры нет столбца в таблице
+    MissingColumn(String),
+    // для столбца таблицы нет поля в структуре
+    UnmappedColumn(String),
+    Nullability {
+        column: String,
+        field_nullable: bool,
+        column_nullable: bool,
+    },
+    PrimaryKey {
+        column: String,
+        field_primary_key: bool,
+        column_primary_key: bool,
+    },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MappingReport {
+    pub table: String,
+    pub diffs: Vec<MappingDiff>,
+}
+
+impl MappingReport {
+    pub fn is_ok(&self) -> bool {
+        self.diffs.is_empty()
+    }
+}
+
+impl fmt::Display for MappingDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MappingDiff::MissingTable => write!(f, "table not found"),
+            MappingDiff::MissingColumn(ref column) => {
+                write!(f, "column `{}` not found in table", column)
+            }
+            MappingDiff::UnmappedColumn(ref column) => {
+                write!(f, "column `{}` is not mapped to any field", column)
+            }
+            MappingDiff::Nullability {
+                ref column,
+                field_nullable,
+                column_nullable,
+            } => write!(
+                f,
+                "column `{}` is {} but field is {}",
+                column,
+                if column_nullable {
+                    "nullable"
+                } else {
+                    "not null"
+                },
+                if field_nullable {
+                    "nullable"
+                } else {
+                    "not null"
+                },
+            ),
+            MappingDiff::PrimaryKey {
+                ref column,
+                column_primary_key,
+                ..
+            } => {
+                if column_primary_key {
+                    write!(f, "column `{}` is a primary key but field is not", column)
+                } else {
+                    write!(f, "field is a primary key but column `{}` is not", column)
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for MappingReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_ok() {
+            return write!(f, "table `{}`: mapping is ok", self.table);
+        }
+        write!(f, "table `{}`:", self.table)?;
+        for diff in self.diffs.iter() {
+            write!(f, "\n- {}", diff)?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn verify_mapping<T: SqlSchema>(
+    conn: &Connection,
+    table: &str,
+) -> rusqlite::Result<MappingReport> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let mut rows = backend::query(&mut stmt, &[])?;
+    let mut actual = Vec::new();
+    while let Some(row) = rows.next()? {
+        let name: String = row.get("name")?;
+        let not_null: bool = row.get("notnull")?;
+        let primary_key = row.get::<_, i64>("pk")? > 0;
+        actual.push(ColumnDef {
+            name,
+            nullable: !not_null,
+            primary_key,
+        });
+    }
+    let mut diffs = Vec::new();
+    if actual.is_empty() {
+        diffs.push(MappingDiff::MissingTable);
+        return Ok(MappingReport {
+            table: table.to_string(),
+            diffs,
+        });
+    }
+    let expected = T::columns();
+    for e in expected.iter() {
+        let a = match actual.iter().find(|a| a.name.eq_ignore_ascii_case(&e.name)) {
+            Some(a) => a,
+            None => {
+                diffs.push(MappingDiff::MissingColumn(e.name.clone()));
+                continue;
+            }
+        };
+        // допустимость NULL первичного ключа не сравнивается: поле ключа может иметь тип
+        // Option<T> (значение до вставки), а ключ INTEGER PRIMARY KEY не бывает NULL
+        if !a.primary_key && e.nullable != a.nullable {
+            diffs.push(MappingDiff::Nullability {
+                column: a.name.clone(),
+                field_nullable: e.nullable,
+                column_nullable: a.nullable,
+            });
+        }
+        if e.primary_key != a.primary_key {
+            diffs.push(MappingDiff::PrimaryKey {
+                column: a.name.clone(),
+                field_primary_key: e.primary_key,
+                column_primary_key: a.primary_key,
+            });
+        }
+    }
+    for a in actual.iter() {
+        if !expected
+            .iter()
+            .any(|e| e.name.eq_ignore_ascii_case(&a.name))
+        {
+            diffs.push(MappingDiff::UnmappedColumn(a.name.clone()));
+        }
+    }
+    Ok(MappingReport {
+        table: table.to_string(),
+        diffs,
+    })
+}
@@ -0,0 +1,40 @@
+// Чтение результатов запроса в структуры, реализующие SqlResult: индексы столбцов
+// вычисляются один раз для запроса, строки читаются по индексам
+use std::marker::PhantomData;
+
+use rusqlite::types::ToSql;
+use rusqlite::{Rows, Statement};
+
+use crate::{backend, SqlResult};
+
+pub struct SelectIter<'stmt, T> {
+    rows: Rows<'stmt>,
+    indices: Vec<Option<usize>>,
+    result: PhantomData<T>,
+}
+
+pub fn select_iter<'stmt, T: SqlResult>(
+    stmt: &'stmt mut Statement<'_>,
+    params: &[(&str, &dyn ToSql)],
+) -> rusqlite::Result<SelectIter<'stmt, T>> {
+    T::check_columns(stmt)?;
+    let indices = T::column_indices(stmt);
+    let rows = backend::query(stmt, params)?;
+    Ok(SelectIter {
+        rows,
+        indices,
+        result: PhantomData,
+    })
+}
+
+impl<T: SqlResult> Iterator for SelectIter<'_, T> {
+    type Item = rusqlite::Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.rows.next() {
+            Ok(Some(row)) => Some(T::from_row_indexed(row, &self.indices)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
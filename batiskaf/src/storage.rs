@@ -0,0 +1,267 @@
+// Способы хранения значений типов из других крейтов, выбираемые атрибутами полей
+// timestamp = "...", uuid = "..." и decimal = "..." (features chrono, time, uuid, rust_decimal)
+use std::error::Error;
+
+use rusqlite::types::{FromSqlError, FromSqlResult, ToSqlOutput, Value, ValueRef};
+
+// Способ хранения значения типа T в столбце
+pub trait Strategy<T> {
+    fn from_sql(value: ValueRef<'_>) -> FromSqlResult<T>;
+    fn to_sql(value: &T) -> rusqlite::Result<ToSqlOutput<'_>>;
+}
+
+// NULL соответствует None
+impl<S: Strategy<T>, T> Strategy<Option<T>> for S {
+    fn from_sql(value: ValueRef<'_>) -> FromSqlResult<Option<T>> {
+        match value {
+            ValueRef::Null => Ok(None),
+            value => S::from_sql(value).map(Some),
+        }
+    }
+
+    fn to_sql(value: &Option<T>) -> rusqlite::Result<ToSqlOutput<'_>> {
+        match *value {
+            Some(ref value) => S::to_sql(value),
+            None => Ok(ToSqlOutput::Owned(Value::Null)),
+        }
+    }
+}
+
+// timestamp = "unix" - целое число секунд от начала эпохи Unix
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub enum Unix {}
+
+// timestamp = "unix_ms" - целое число миллисекунд от начала эпохи Unix
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub enum UnixMs {}
+
+// timestamp = "rfc3339" - строка в формате RFC 3339 в UTC
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub enum Rfc3339 {}
+
+// uuid = "blob" - 16 байт
+#[cfg(feature = "uuid")]
+pub enum Blob {}
+
+// uuid = "text", decimal = "text" - строка
+#[cfg(any(feature = "uuid", feature = "rust_decimal"))]
+pub enum Text {}
+
+// decimal = "cents" - целое число сотых долей
+#[cfg(feature = "rust_decimal")]
+pub enum Cents {}
+
+fn conversion_error<E: Into<Box<dyn Error + Send + Sync>>>(err: E) -> FromSqlError {
+    FromSqlError::Other(err.into())
+}
+
+#[cfg(any(feature = "chrono", feature = "time", feature = "rust_decimal"))]
+fn to_sql_error<E: Into<Box<dyn Error + Send + Sync>>>(err: E) -> rusqlite::Error {
+    rusqlite::Error::ToSqlConversionFailure(err.into())
+}
+
+// Момент времени; типы без часового пояса считаются временем в UTC
+#[cfg(any(feature = "chrono", feature = "time"))]
+pub trait Timestamp: Sized {
+    fn from_unix_nanos(nanos: i128) -> Option<Self>;
+    fn unix_nanos(&self) -> i128;
+    fn parse_rfc3339(s: &str) -> Result<Self, Box<dyn Error + Send + Sync>>;
+    fn format_rfc3339(&self) -> Result<String, Box<dyn Error + Send + Sync>>;
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn from_unix<T: Timestamp>(value: ValueRef<'_>, nanos_per_unit: i128) -> FromSqlResult<T> {
+    let x = value.as_i64()?;
+    T::from_unix_nanos(i128::from(x) * nanos_per_unit).ok_or(FromSqlError::OutOfRange(x))
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn to_unix<T: Timestamp>(value: &T, nanos_per_unit: i128) -> rusqlite::Result<ToSqlOutput<'_>> {
+    let x = value.unix_nanos().div_euclid(nanos_per_unit);
+    if x < i128::from(i64::MIN) || x > i128::from(i64::MAX) {
+        return Err(to_sql_error("timestamp is out of range"));
+    }
+    Ok(ToSqlOutput::Owned(Value::Integer(x as i64)))
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+impl<T: Timestamp> Strategy<T> for Unix {
+    fn from_sql(value: ValueRef<'_>) -> FromSqlResult<T> {
+        from_unix(value, 1_000_000_000)
+    }
+
+    fn to_sql(value: &T) -> rusqlite::Result<ToSqlOutput<'_>> {
+        to_unix(value, 1_000_000_000)
+    }
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+impl<T: Timestamp> Strategy<T> for UnixMs {
+    fn from_sql(value: ValueRef<'_>) -> FromSqlResult<T> {
+        from_unix(value, 1_000_000)
+    }
+
+    fn to_sql(value: &T) -> rusqlite::Result<ToSqlOutput<'_>> {
+        to_unix(value, 1_000_000)
+    }
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+impl<T: Timestamp> Strategy<T> for Rfc3339 {
+    fn from_sql(value: ValueRef<'_>) -> FromSqlResult<T> {
+        T::parse_rfc3339(value.as_str()?).map_err(conversion_error)
+    }
+
+    fn to_sql(value: &T) -> rusqlite::Result<ToSqlOutput<'_>> {
+        let s = value.format_rfc3339().map_err(to_sql_error)?;
+        Ok(ToSqlOutput::Owned(Value::Text(s)))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for chrono::DateTime<chrono::Utc> {
+    fn from_unix_nanos(nanos: i128) -> Option<Self> {
+        let secs = nanos.div_euclid(1_000_000_000);
+        let nsecs = nanos.rem_euclid(1_000_000_000) as u32;
+        if secs < i128::from(i64::MIN) || secs > i128::from(i64::MAX) {
+            return None;
+        }
+        chrono::DateTime::from_timestamp(secs as i64, nsecs)
+    }
+
+    fn unix_nanos(&self) -> i128 {
+        i128::from(self.timestamp()) * 1_000_000_000 + i128::from(self.timestamp_subsec_nanos())
+    }
+
+    fn parse_rfc3339(s: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(chrono::DateTime::parse_from_rfc3339(s)?.with_timezone(&chrono::Utc))
+    }
+
+    fn format_rfc3339(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(self.to_rfc3339_opts(chrono::SecondsFormat::AutoSi, true))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for chrono::NaiveDateTime {
+    fn from_unix_nanos(nanos: i128) -> Option<Self> {
+        chrono::DateTime::<chrono::Utc>::from_unix_nanos(nanos).map(|t| t.naive_utc())
+    }
+
+    fn unix_nanos(&self) -> i128 {
+        self.and_utc().unix_nanos()
+    }
+
+    fn parse_rfc3339(s: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        chrono::DateTime::<chrono::Utc>::parse_rfc3339(s).map(|t| t.naive_utc())
+    }
+
+    fn format_rfc3339(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+        self.and_utc().format_rfc3339()
+    }
+}
+
+#[cfg(feature = "time")]
+impl Timestamp for time::OffsetDateTime {
+    fn from_unix_nanos(nanos: i128) -> Option<Self> {
+        time::OffsetDateTime::from_unix_timestamp_nanos(nanos).ok()
+    }
+
+    fn unix_nanos(&self) -> i128 {
+        self.unix_timestamp_nanos()
+    }
+
+    fn parse_rfc3339(s: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let t = time::OffsetDateTime::parse(s, &time::format_description::well_known::Rfc3339)?;
+        Ok(t.to_offset(time::UtcOffset::UTC))
+    }
+
+    fn format_rfc3339(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+        Ok(self
+            .to_offset(time::UtcOffset::UTC)
+            .format(&time::format_description::well_known::Rfc3339)?)
+    }
+}
+
+#[cfg(feature = "time")]
+impl Timestamp for time::PrimitiveDateTime {
+    fn from_unix_nanos(nanos: i128) -> Option<Self> {
+        let t = time::OffsetDateTime::from_unix_nanos(nanos)?;
+        Some(time::PrimitiveDateTime::new(t.date(), t.time()))
+    }
+
+    fn unix_nanos(&self) -> i128 {
+        self.assume_utc().unix_nanos()
+    }
+
+    fn parse_rfc3339(s: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let t = time::OffsetDateTime::parse_rfc3339(s)?;
+        Ok(time::PrimitiveDateTime::new(t.date(), t.time()))
+    }
+
+    fn format_rfc3339(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+        self.assume_utc().format_rfc3339()
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl Strategy<uuid::Uuid> for Blob {
+    fn from_sql(value: ValueRef<'_>) -> FromSqlResult<uuid::Uuid> {
+        uuid::Uuid::from_slice(value.as_blob()?).map_err(conversion_error)
+    }
+
+    fn to_sql(value: &uuid::Uuid) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Borrowed(ValueRef::Blob(value.as_bytes())))
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl Strategy<uuid::Uuid> for Text {
+    fn from_sql(value: ValueRef<'_>) -> FromSqlResult<uuid::Uuid> {
+        uuid::Uuid::parse_str(value.as_str()?).map_err(conversion_error)
+    }
+
+    fn to_sql(value: &uuid::Uuid) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Owned(Value::Text(
+            value.hyphenated().to_string(),
+        )))
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl Strategy<rust_decimal::Decimal> for Text {
+    fn from_sql(value: ValueRef<'_>) -> FromSqlResult<rust_decimal::Decimal> {
+        match value {
+            ValueRef::Integer(i) => Ok(rust_decimal::Decimal::from(i)),
+            value => value.as_str()?.parse().map_err(conversion_error),
+        }
+    }
+
+    fn to_sql(value: &rust_decimal::Decimal) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Owned(Value::Text(value.to_string())))
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+impl Strategy<rust_decimal::Decimal> for Cents {
+    fn from_sql(value: ValueRef<'_>) -> FromSqlResult<rust_decimal::Decimal> {
+        Ok(rust_decimal::Decimal::new(value.as_i64()?, 2))
+    }
+
+    fn to_sql(value: &rust_decimal::Decimal) -> rusqlite::Result<ToSqlOutput<'_>> {
+        use rust_decimal::prelude::ToPrimitive;
+        let cents = value.checked_mul(rust_decimal::Decimal::ONE_HUNDRED);
+        if let Some(ref cents) = cents {
+            if !cents.fract().is_zero() {
+                return Err(to_sql_error(format!(
+                    "decimal {} has more than two fractional digits",
+                    value
+                )));
+            }
+        }
+        match cents.and_then(|cents| cents.to_i64()) {
+            Some(cents) => Ok(ToSqlOutput::Owned(Value::Integer(cents))),
+            None => Err(to_sql_error(format!("decimal {} is out of range", value))),
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use rusqlite::{Connection, NO_PARAMS};
+use rusqlite::Connection;
 
 use super::*;
 
@@ -10,7 +10,7 @@ struct Person {
 }
 
 impl SqlParam for Person {
-    fn to_named_params(&self, stmt: &Statement) -> Vec<(&str, &dyn ToSql)> {
+    fn to_named_params(&self, stmt: &Statement<'_>) -> Vec<(&str, &dyn ToSql)> {
         let mut params = Vec::new();
         if let Ok(Some(_)) = stmt.parameter_index(":id") {
             params.push((":id", &self.id as &dyn ToSql));
@@ -26,7 +26,7 @@ impl SqlParam for Person {
 }
 
 impl SqlResult for Person {
-    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+    fn from_row(row: &Row<'_>) -> rusqlite::Result<Self> {
         Ok(Person {
             id: row.get("id")?,
             name: row.get("name")?,
@@ -56,10 +56,39 @@ impl SqlDelete for Person {
     }
 }
 
+impl SqlSchema for Person {
+    fn create_table_statement(table: &str) -> String {
+        format!(
+            "create table {} (id integer primary key, name text not null, age integer)",
+            table
+        )
+    }
+
+    fn columns() -> Vec<ColumnDef> {
+        vec![
+            ColumnDef {
+                name: "id".to_string(),
+                nullable: false,
+                primary_key: true,
+            },
+            ColumnDef {
+                name: "name".to_string(),
+                nullable: false,
+                primary_key: false,
+            },
+            ColumnDef {
+                name: "age".to_string(),
+                nullable: true,
+                primary_key: false,
+            },
+        ]
+    }
+}
+
 fn create_table(conn: &Connection) {
     conn.execute(
         "create table person(id integer primary key, name text not null, age integer)",
-        NO_PARAMS,
+        [],
     )
     .unwrap();
 }
@@ -76,15 +105,43 @@ fn test_sql_param() {
         name: "Bob".to_string(),
         age: Some(30),
     };
-    let params = person.to_named_params(&stmt);
-    stmt.execute_named(&params).unwrap();
+    let params = person.to_params(&stmt);
+    stmt.execute(params).unwrap();
     let mut select = conn.prepare("select id, name, age from person").unwrap();
     let x = select
-        .query_row(NO_PARAMS, |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .query_row([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
         .unwrap();
     assert_eq!((1, "Bob".to_string(), 30), x);
 }
 
+#[test]
+fn test_to_params() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    let mut stmt = conn
+        .prepare("insert into person (age, name) values (:age, coalesce(?, :name))")
+        .unwrap();
+    let person = Person {
+        id: 0,
+        name: "Bob".to_string(),
+        age: Some(30),
+    };
+    stmt.execute(person.to_params(&stmt)).unwrap();
+    let mut select = conn
+        .prepare("select id, name, age from person where name = :name and age = :age")
+        .unwrap();
+    let mut rows = select.query(person.to_params(&select)).unwrap();
+    let row = rows.next().unwrap().unwrap();
+    assert_eq!(
+        Person {
+            id: 1,
+            name: "Bob".to_string(),
+            age: Some(30),
+        },
+        Person::from_row(row).unwrap()
+    );
+}
+
 #[test]
 fn test_sql_result() {
     let conn = Connection::open_in_memory().unwrap();
@@ -92,10 +149,10 @@ fn test_sql_result() {
     let mut stmt = conn
         .prepare("insert into person (name, age) values (:name, :age)")
         .unwrap();
-    stmt.execute_named(&[(":name", &"Alice" as &dyn ToSql), (":age", &33)])
+    stmt.execute(&[(":name", &"Alice" as &dyn ToSql), (":age", &33)])
         .unwrap();
     let mut select = conn.prepare("select id, name, age from person").unwrap();
-    let x = select.query_row(NO_PARAMS, Person::from_row).unwrap();
+    let x = select.query_row([], Person::from_row).unwrap();
     assert_eq!(
         Person {
             id: 1,
@@ -113,10 +170,10 @@ fn test_sql_result_2() {
     let mut stmt = conn
         .prepare("insert into person (name, age) values (:name, :age)")
         .unwrap();
-    stmt.execute_named(&[(":name", &"Alice" as &dyn ToSql), (":age", &33)])
+    stmt.execute(&[(":name", &"Alice" as &dyn ToSql), (":age", &33)])
         .unwrap();
     let mut select = conn.prepare("select id, name from person").unwrap();
-    let x = select.query_row(NO_PARAMS, Person::from_row).unwrap();
+    let x = select.query_row([], Person::from_row).unwrap();
     assert_eq!(
         Person {
             id: 1,
@@ -134,7 +191,7 @@ fn test_select_one() {
     let mut stmt = conn
         .prepare("insert into person (name, age) values (:name, :age)")
         .unwrap();
-    stmt.execute_named(&[(":name", &"Alice" as &dyn ToSql), (":age", &33)])
+    stmt.execute(&[(":name", &"Alice" as &dyn ToSql), (":age", &33)])
         .unwrap();
     let x: Person = conn
         .select_one("select id, name, age from person", &[])
@@ -166,14 +223,80 @@ fn test_select_many() {
     let mut stmt = conn
         .prepare("insert into person (name, age) values (:name, :age)")
         .unwrap();
-    stmt.execute_named(&alice.to_named_params(&stmt)).unwrap();
-    stmt.execute_named(&bob.to_named_params(&stmt)).unwrap();
+    stmt.execute(alice.to_params(&stmt)).unwrap();
+    stmt.execute(bob.to_params(&stmt)).unwrap();
     let people = conn
         .select_many("select id, name, age from person", &[])
         .unwrap();
     assert_eq!(vec![alice, bob], people);
 }
 
+#[test]
+fn test_dyn_row() {
+    use rusqlite::types::Value;
+
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    conn.execute_batch("insert into person (name, age) values ('Alice', 33), ('Bob', null)")
+        .unwrap();
+    let rows: Vec<DynRow> = conn
+        .select_many(
+            "select name, age, id * 1.5 as x from person order by id",
+            &[],
+        )
+        .unwrap();
+    assert_eq!(2, rows.len());
+    assert_eq!(&["name", "age", "x"], rows[0].columns());
+    assert_eq!(
+        &[
+            Value::Text("Bob".to_string()),
+            Value::Null,
+            Value::Real(3.0)
+        ],
+        rows[1].values()
+    );
+    assert_eq!("Alice", rows[0].get::<String>("name").unwrap());
+    assert_eq!(Some(33), rows[0].get::<Option<u32>>("age").unwrap());
+    assert_eq!(None, rows[1].get::<Option<u32>>("age").unwrap());
+    assert_eq!(1.5, rows[0].get_at::<f64>(2).unwrap());
+    assert_eq!(Some(&Value::Integer(33)), rows[0].value("age"));
+    match rows[0].get::<i64>("email") {
+        Err(rusqlite::Error::InvalidColumnName(ref name)) if name == "email" => (),
+        x => panic!("unexpected result: {:?}", x),
+    }
+    match rows[0].get::<i64>("name") {
+        Err(rusqlite::Error::InvalidColumnType(0, ref name, rusqlite::types::Type::Text))
+            if name == "name" => {}
+        x => panic!("unexpected result: {:?}", x),
+    }
+    let row: DynRow = conn
+        .select_one("select count(*) as n from person", &[])
+        .unwrap();
+    assert_eq!(2, row.get::<i64>("n").unwrap());
+    let row: DynRow = conn
+        .select_one("select id as ID, name as Name from person order by id", &[])
+        .unwrap();
+    assert_eq!(&["ID", "Name"], row.columns());
+    assert_eq!(1, row.get::<i64>("id").unwrap());
+    assert_eq!(Some(1), row.index("NAME"));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_dyn_row_serialize() {
+    let conn = Connection::open_in_memory().unwrap();
+    let row: DynRow = conn
+        .select_one(
+            "select 1 as id, 'Alice' as name, null as age, 2.5 as score, x'0102' as data",
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        r#"{"id":1,"name":"Alice","age":null,"score":2.5,"data":[1,2]}"#,
+        serde_json::to_string(&row).unwrap()
+    );
+}
+
 #[test]
 fn test_insert() {
     let conn = Connection::open_in_memory().unwrap();
@@ -220,7 +343,519 @@ fn test_delete() {
     };
     bob.id = conn.insert("person", &bob).unwrap();
     let mut select = conn.prepare("select id, name, age from person").unwrap();
-    assert!(select.exists(NO_PARAMS).unwrap());
+    assert!(select.exists([]).unwrap());
     conn.delete("person", &bob).unwrap();
-    assert!(!select.exists(NO_PARAMS).unwrap());
+    assert!(!select.exists([]).unwrap());
+}
+
+#[test]
+fn test_params() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    let mut stmt = conn
+        .prepare("insert into person (name, age) values (:name, :age)")
+        .unwrap();
+    let name = "Bob".to_string();
+    let params = params! { id => 0, name => &name, :age => 30 };
+    stmt.execute(params.to_params(&stmt)).unwrap();
+    let x: Person = conn
+        .select_one(
+            "select id, name, age from person where name = :name",
+            &params! { name => name },
+        )
+        .unwrap();
+    assert_eq!(
+        Person {
+            id: 1,
+            name: "Bob".to_string(),
+            age: Some(30)
+        },
+        x
+    );
+    let people: Vec<Person> = conn
+        .select_many("select id, name, age from person", &params! {})
+        .unwrap();
+    assert_eq!(vec![x], people);
+}
+
+#[test]
+fn test_dynamic_sql() {
+    let name: Option<String> = None;
+    let age = Some(30);
+    let ids = vec![1, 2, 3];
+    let mut sql = DynamicSql::new("select id, name, age from person");
+    sql.where_clause(|w| {
+        w.push_some("name = :name", "name", &name);
+        w.push_some("age >= :age", "age", &age);
+        w.push_in("id in (:ids)", "ids", &ids);
+    })
+    .push("order by id");
+    assert_eq!(
+        "select id, name, age from person WHERE age >= :age AND id in (:ids_0, :ids_1, :ids_2) order by id",
+        sql.sql()
+    );
+    let names: Vec<&str> = sql.params().into_iter().map(|(n, _)| n).collect();
+    assert_eq!(vec![":age", ":ids_0", ":ids_1", ":ids_2"], names);
+
+    // параметры в комментариях не заменяются
+    let mut sql = DynamicSql::new("select id from person");
+    sql.where_clause(|w| {
+        w.push_in("/* :ids */ id in (:ids)", "ids", &ids);
+    });
+    assert_eq!(
+        "select id from person WHERE /* :ids */ id in (:ids_0, :ids_1, :ids_2)",
+        sql.sql()
+    );
+    assert_eq!(
+        "select id from person -- :ids\nwhere id in (:ids_0)",
+        convert::expand_parameter(
+            Cow::Borrowed("select id from person -- :ids\nwhere id in (:ids)"),
+            ":ids",
+            1
+        )
+    );
+
+    let mut sql = DynamicSql::new("select id, name, age from person");
+    sql.where_clause(|w| {
+        w.push_some("name = :name", "name", &name);
+        w.push_if(false, "age is null");
+    });
+    assert_eq!("select id, name, age from person", sql.sql());
+    assert!(sql.params().is_empty());
+}
+
+#[test]
+fn test_dynamic_sql_select() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    for (name, age) in &[("Alice", Some(33)), ("Bob", Some(30)), ("Carol", None)] {
+        conn.insert(
+            "person",
+            &Person {
+                id: 0,
+                name: name.to_string(),
+                age: *age,
+            },
+        )
+        .unwrap();
+    }
+    let ids = vec![1, 3];
+    let min_age = Some(31);
+    let mut sql = DynamicSql::new("select id, name, age from person");
+    sql.where_clause(|w| {
+        w.push_in("id in (:ids)", ":ids", &ids);
+        w.or(|o| {
+            o.push_some("age >= :min_age", "min_age", &min_age);
+            o.push("age is null");
+        });
+    })
+    .push("order by id");
+    let people: Vec<Person> = conn.select_many(&sql.sql(), &sql.params()).unwrap();
+    let names: Vec<String> = people.into_iter().map(|p| p.name).collect();
+    assert_eq!(vec!["Alice".to_string(), "Carol".to_string()], names);
+
+    let name = "Bob".to_string();
+    let age: Option<u32> = None;
+    let mut sql = DynamicSql::new("update person");
+    sql.set_clause(|s| {
+        s.push("name = :name").bind("name", &name);
+        s.push_some("age = :age", "age", &age);
+    })
+    .push("where id = :id")
+    .bind("id", &2);
+    assert_eq!("update person SET name = :name where id = :id", sql.sql());
+    let mut stmt = conn.prepare(&sql.sql()).unwrap();
+    assert_eq!(1, stmt.execute(sql.to_params(&stmt)).unwrap());
+}
+
+#[test]
+fn test_verify_mapping() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    let report = conn.verify_mapping::<Person>("person").unwrap();
+    assert!(report.is_ok());
+    conn.execute(
+        "create table person_v2 (id integer, name text, email text)",
+        [],
+    )
+    .unwrap();
+    let report = conn.verify_mapping::<Person>("person_v2").unwrap();
+    assert_eq!(
+        vec![
+            MappingDiff::Nullability {
+                column: "id".to_string(),
+                field_nullable: false,
+                column_nullable: true,
+            },
+            MappingDiff::PrimaryKey {
+                column: "id".to_string(),
+                field_primary_key: true,
+                column_primary_key: false,
+            },
+            MappingDiff::Nullability {
+                column: "name".to_string(),
+                field_nullable: false,
+                column_nullable: true,
+            },
+            MappingDiff::MissingColumn("age".to_string()),
+            MappingDiff::UnmappedColumn("email".to_string()),
+        ],
+        report.diffs
+    );
+    let report = conn.verify_mapping::<Person>("nobody").unwrap();
+    assert_eq!(vec![MappingDiff::MissingTable], report.diffs);
+}
+
+#[test]
+fn test_migrate() {
+    use crate::migrate::{Error, Migrator};
+    let v1 = [(
+        "create_person",
+        "create table person (id integer primary key, name text not null)",
+    )];
+    let v2 = [
+        v1[0],
+        ("add_age", "alter table person add column age integer"),
+    ];
+    let mut conn = Connection::open_in_memory().unwrap();
+    assert_eq!(
+        vec!["create_person"],
+        Migrator::new(&v1).run(&mut conn).unwrap()
+    );
+    assert_eq!(vec!["add_age"], Migrator::new(&v2).run(&mut conn).unwrap());
+    assert!(Migrator::new(&v2).run(&mut conn).unwrap().is_empty());
+    assert_eq!(2, Migrator::new(&v2).version(&conn).unwrap());
+    conn.execute("insert into person (name, age) values ('Bob', 30)", [])
+        .unwrap();
+
+    let edited = [v1[0], ("add_age", "alter table person add column age text")];
+    match Migrator::new(&edited).run(&mut conn) {
+        Err(Error::ChecksumMismatch { version, name, .. }) => {
+            assert_eq!((2, "add_age".to_string()), (version, name))
+        }
+        x => panic!("unexpected result: {:?}", x),
+    }
+    match Migrator::new(&v1).run(&mut conn) {
+        Err(Error::VersionTooNew { current, latest }) => assert_eq!((2, 1), (current, latest)),
+        x => panic!("unexpected result: {:?}", x),
+    }
+}
+
+#[test]
+fn test_migrate_rollback() {
+    use crate::migrate::Migrator;
+    let migrations = [
+        (
+            "create_person",
+            "create table person (id integer primary key)",
+        ),
+        (
+            "broken",
+            "create table other (id integer); insert into nowhere values (1)",
+        ),
+    ];
+    let mut conn = Connection::open_in_memory().unwrap();
+    let migrator = Migrator::new(&migrations);
+    assert!(migrator.run(&mut conn).is_err());
+    assert_eq!(1, migrator.version(&conn).unwrap());
+    let tables: i64 = conn
+        .query_row(
+            "select count(*) from sqlite_master where name = 'other'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(0, tables);
+}
+
+#[test]
+fn test_migrate_user_version() {
+    use crate::migrate::{Migrator, Tracker};
+    let migrations = [
+        (
+            "create_person",
+            "create table person (id integer primary key)",
+        ),
+        ("add_name", "alter table person add column name text"),
+    ];
+    let mut conn = Connection::open_in_memory().unwrap();
+    let migrator = Migrator::new(&migrations[..1]).tracker(Tracker::UserVersion);
+    assert_eq!(vec!["create_person"], migrator.run(&mut conn).unwrap());
+    let migrator = Migrator::new(&migrations).tracker(Tracker::UserVersion);
+    assert_eq!(vec!["add_name"], migrator.run(&mut conn).unwrap());
+    let version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(2, version);
+    let tables: i64 = conn
+        .query_row(
+            "select count(*) from sqlite_master where name = '__batiskaf_migrations'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(0, tables);
+}
+
+#[cfg(feature = "pool")]
+fn temp_db(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("batiskaf_{}_{}.db", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[cfg(feature = "pool")]
+#[test]
+fn test_pool() {
+    use crate::pool::Pool;
+    use std::thread;
+    let path = temp_db("pool");
+    let db = path.clone();
+    let pool = Pool::builder(move || Connection::open(&db))
+        .max_size(2)
+        .on_connect(|conn| conn.execute_batch("PRAGMA foreign_keys = ON"))
+        .build();
+    create_table(&pool.get().unwrap());
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let pool = pool.clone();
+            thread::spawn(move || {
+                let conn = pool.get().unwrap();
+                let foreign_keys: bool = conn
+                    .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+                    .unwrap();
+                assert!(foreign_keys);
+                conn.insert(
+                    "person",
+                    &Person {
+                        id: 0,
+                        name: format!("Person {}", i),
+                        age: None,
+                    },
+                )
+                .unwrap();
+            })
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+    let people: Vec<Person> = pool
+        .get()
+        .unwrap()
+        .select_many("select id, name, age from person", &[])
+        .unwrap();
+    assert_eq!(4, people.len());
+    let (size, idle) = pool.state();
+    assert!(size <= 2);
+    assert_eq!(size, idle);
+    drop(pool);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[cfg(feature = "pool")]
+#[test]
+fn test_pool_timeout() {
+    use crate::pool::{Error, Pool};
+    use std::time::Duration;
+    let pool = Pool::builder(Connection::open_in_memory)
+        .max_size(1)
+        .timeout(Duration::from_millis(10))
+        .build();
+    let conn = pool.get().unwrap();
+    match pool.get() {
+        Err(Error::Timeout) => (),
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("connection must not be available"),
+    }
+    drop(conn);
+    assert!(pool.get().is_ok());
+}
+
+#[cfg(feature = "r2d2")]
+#[test]
+fn test_r2d2() {
+    use crate::pool::{Connector, SqliteConnectionManager};
+    let path = temp_db("r2d2");
+    let db = path.clone();
+    let manager = SqliteConnectionManager::new(
+        Connector::new(move || Connection::open(&db))
+            .on_connect(|conn| conn.execute_batch("PRAGMA foreign_keys = ON")),
+    );
+    let pool = r2d2::Pool::builder().max_size(2).build(manager).unwrap();
+    let conn = pool.get().unwrap();
+    create_table(&conn);
+    let id = conn
+        .insert(
+            "person",
+            &Person {
+                id: 0,
+                name: "Bob".to_string(),
+                age: Some(30),
+            },
+        )
+        .unwrap();
+    let bob: Person = pool
+        .get()
+        .unwrap()
+        .select_one(
+            "select id, name, age from person where id = :id",
+            &[(":id", &id)],
+        )
+        .unwrap();
+    assert_eq!("Bob", bob.name);
+    drop(conn);
+    drop(pool);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[cfg(feature = "async")]
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+    use std::thread::{self, Thread};
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_async_connection() {
+    block_on(async {
+        let conn = AsyncConnection::open_in_memory().await.unwrap();
+        conn.call(|conn| {
+            create_table(conn);
+            Ok(())
+        })
+        .await
+        .unwrap();
+        let id = conn
+            .insert(
+                "person",
+                Person {
+                    id: 0,
+                    name: "Alice".to_string(),
+                    age: None,
+                },
+            )
+            .await
+            .unwrap();
+        let name = "Alice".to_string();
+        let mut alice: Person = conn
+            .select_one(
+                "select id, name, age from person where name = :name",
+                &[(":name", &name)],
+            )
+            .await
+            .unwrap();
+        assert_eq!(id, alice.id);
+        alice.age = Some(25);
+        assert_eq!(1, conn.update("person", alice).await.unwrap());
+        let people: Vec<Person> = conn
+            .select_many("select id, name, age from person", &[])
+            .await
+            .unwrap();
+        assert_eq!(
+            vec![Person {
+                id,
+                name: "Alice".to_string(),
+                age: Some(25),
+            }],
+            people
+        );
+        assert!(conn
+            .verify_mapping::<Person>("person")
+            .await
+            .unwrap()
+            .is_ok());
+        let alice = people.into_iter().next().unwrap();
+        assert_eq!(1, conn.delete("person", alice).await.unwrap());
+        let count: i64 = conn
+            .call(|conn| conn.query_row("select count(*) from person", [], |row| row.get(0)))
+            .await
+            .unwrap();
+        assert_eq!(0, count);
+    });
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_async_connection_panic() {
+    block_on(async {
+        let conn = AsyncConnection::open_in_memory().await.unwrap();
+        let result: rusqlite::Result<()> = conn.call(|_| panic!("boom")).await;
+        assert!(result.is_err());
+        assert_eq!(1, conn.call(|_| Ok(1)).await.unwrap());
+    });
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_async_connection_with() {
+    block_on(async {
+        let conn = AsyncConnection::open_in_memory().await.unwrap();
+        conn.call(|conn| {
+            create_table(conn);
+            Ok(())
+        })
+        .await
+        .unwrap();
+        let alice = Person {
+            id: 1,
+            name: "Alice".to_string(),
+            age: Some(33),
+        };
+        let sql = "insert into person (id, name, age) values (:id, :name, :age)";
+        assert_eq!(1, conn.execute_with(sql, alice).await.unwrap());
+        let filter = Person {
+            id: 1,
+            name: String::new(),
+            age: None,
+        };
+        let sql = "select id, name, age from person where id = :id";
+        let alice: Person = conn.select_one_with(sql, filter).await.unwrap();
+        assert_eq!("Alice", alice.name);
+        let people: Vec<Person> = conn.select_many_with(sql, alice).await.unwrap();
+        assert_eq!(1, people.len());
+    });
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_async_connection_send() {
+    let conn = block_on(AsyncConnection::open_in_memory()).unwrap();
+    block_on(conn.call(|conn| {
+        create_table(conn);
+        conn.execute_batch("insert into person (name) values ('Alice')")
+    }))
+    .unwrap();
+    // future не заимствует ни соединение, ни параметры и выполняется в другом потоке
+    let future = {
+        let name = "Alice".to_string();
+        conn.select_many::<Person>(
+            "select id, name, age from person where name = :name",
+            &[(":name", &name)],
+        )
+    };
+    let people = std::thread::spawn(move || block_on(future))
+        .join()
+        .unwrap()
+        .unwrap();
+    assert_eq!(1, people.len());
 }
@@ -0,0 +1,79 @@
+// Сравнение чтения строк по именам столбцов (SqlResult::from_row)
+// и по индексам, вычисленным один раз для запроса (select_iter)
+//
+// cargo bench -p batiskaf_derive --bench select
+use std::time::{Duration, Instant};
+
+use rusqlite::Connection;
+
+use batiskaf::SqlResult;
+use batiskaf_derive::*;
+
+#[allow(unused)]
+#[derive(SqlResult)]
+struct Order {
+    pub id: i64,
+    pub customer: String,
+    pub product: String,
+    pub quantity: i64,
+    pub price: f64,
+    pub discount: Option<f64>,
+    pub comment: Option<String>,
+    pub created: i64,
+}
+
+const ROWS: usize = 100_000;
+const SQL: &str =
+    "select id, customer, product, quantity, price, discount, comment, created from orders";
+
+fn by_name(conn: &Connection) -> Vec<Order> {
+    let mut stmt = conn.prepare(SQL).unwrap();
+    let mut rows = stmt.query([]).unwrap();
+    let mut result = Vec::with_capacity(ROWS);
+    while let Some(row) = rows.next().unwrap() {
+        result.push(Order::from_row(row).unwrap());
+    }
+    result
+}
+
+fn by_index(conn: &Connection) -> Vec<Order> {
+    let mut stmt = conn.prepare(SQL).unwrap();
+    batiskaf::select_iter(&mut stmt, &[])
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap()
+}
+
+fn measure(name: &str, conn: &Connection, f: fn(&Connection) -> Vec<Order>) -> Duration {
+    assert_eq!(ROWS, f(conn).len());
+    let mut best = Duration::MAX;
+    for _ in 0..5 {
+        let start = Instant::now();
+        let orders = f(conn);
+        best = best.min(start.elapsed());
+        assert_eq!(ROWS, orders.len());
+    }
+    println!("{:<10} {:>10.2?}", name, best);
+    best
+}
+
+fn main() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(&format!(
+        "create table orders (id integer primary key, customer text not null, product text not null,
+            quantity integer not null, price real not null, discount real, comment text, created integer not null);
+         with recursive n(x) as (select 1 union all select x + 1 from n where x < {})
+         insert into orders (customer, product, quantity, price, discount, comment, created)
+         select 'customer ' || x, 'product ' || (x % 100), x % 10, x * 0.5,
+             case when x % 3 = 0 then 0.1 end, case when x % 5 = 0 then 'comment' end, 1600000000 + x
+         from n;",
+        ROWS
+    ))
+    .unwrap();
+    let name = measure("by name", &conn, by_name);
+    let index = measure("by index", &conn, by_index);
+    println!(
+        "speedup    {:>10.2}x",
+        name.as_secs_f64() / index.as_secs_f64()
+    );
+}
@@ -0,0 +1,185 @@
+#[cfg(feature = "check")]
+use std::env;
+#[cfg(feature = "check")]
+use std::fs;
+#[cfg(feature = "check")]
+use std::path::PathBuf;
+
+use proc_macro2::Span;
+#[cfg(feature = "check")]
+use syn::Ident;
+use syn::{self, DeriveInput};
+
+#[cfg(feature = "check")]
+use crate::column::{columns_with_fields, parse_attributes};
+
+// Проверка сгенерированного запроса по схеме БД, указанной в атрибутах структуры:
+// #[batiskaf(schema = "schema.sql", table = "person")]
+#[cfg(feature = "check")]
+pub(crate) fn check_table(input: &DeriveInput, sql: &str) -> syn::Result<()> {
+    let attrs = parse_attributes(&input.attrs);
+    let (schema, table) = match (
+        attrs.name_value.get("schema"),
+        attrs.name_value.get("table"),
+    ) {
+        (Some(schema), Some(table)) => (schema, table),
+        (None, None) => return Ok(()),
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "`schema` and `table` attributes must be specified together",
+            ));
+        }
+    };
+    let conn = open_schema(schema, input.ident.span())?;
+    let table_columns = table_columns(&conn, table, &input.ident)?;
+    for (c, f) in columns_with_fields(&input.data, false) {
+        if c.skip() {
+            continue;
+        }
+        if !table_columns
+            .iter()
+            .any(|x| x.eq_ignore_ascii_case(&c.name()))
+        {
+            let span = match f.ident {
+                Some(ref ident) => ident.span(),
+                None => input.ident.span(),
+            };
+            return Err(syn::Error::new(
+                span,
+                format!("column `{}` not found in table `{}`", c.name(), table),
+            ));
+        }
+    }
+    prepare(&conn, &sql.replace("{}", table), input.ident.span())
+}
+
+#[cfg(not(feature = "check"))]
+pub(crate) fn check_table(_input: &DeriveInput, _sql: &str) -> syn::Result<()> {
+    Ok(())
+}
+
+#[cfg(feature = "check")]
+pub(crate) fn check_statement(schema: &str, sql: &str, span: Span) -> syn::Result<()> {
+    let conn = open_schema(schema, span)?;
+    prepare(&conn, sql, span)
+}
+
+#[cfg(not(feature = "check"))]
+pub(crate) fn check_statement(_schema: &str, _sql: &str, _span: Span) -> syn::Result<()> {
+    Ok(())
+}
+
+#[cfg(feature = "check")]
+fn open_schema(schema: &str, span: Span) -> syn::Result<rusqlite::Connection> {
+    let mut path = match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::new(),
+    };
+    path.push(schema);
+    let text = fs::read_to_string(&path)
+        .map_err(|e| syn::Error::new(span, format!("cannot read `{}`: {}", path.display(), e)))?;
+    let conn =
+        rusqlite::Connection::open_in_memory().map_err(|e| syn::Error::new(span, e.to_string()))?;
+    conn.execute_batch(&text)
+        .map_err(|e| syn::Error::new(span, format!("invalid schema `{}`: {}", schema, e)))?;
+    Ok(conn)
+}
+
+#[cfg(feature = "check")]
+fn table_columns(
+    conn: &rusqlite::Connection,
+    table: &str,
+    name: &Ident,
+) -> syn::Result<Vec<String>> {
+    let error = |e: rusqlite::Error| syn::Error::new(name.span(), e.to_string());
+    let mut stmt = conn
+        .prepare(&format!("PRAGMA table_info({})", table))
+        .map_err(error)?;
+    // пустой список параметров, который принимают все поддерживаемые версии rusqlite
+    let no_params: &[&dyn rusqlite::types::ToSql] = &[];
+    let mut rows = stmt.query(no_params).map_err(error)?;
+    let mut columns = Vec::new();
+    while let Some(row) = rows.next().map_err(error)? {
+        columns.push(row.get::<_, String>("name").map_err(error)?);
+    }
+    if columns.is_empty() {
+        return Err(syn::Error::new(
+            name.span(),
+            format!("table `{}` not found in schema", table),
+        ));
+    }
+    Ok(columns)
+}
+
+#[cfg(feature = "check")]
+fn prepare(conn: &rusqlite::Connection, sql: &str, span: Span) -> syn::Result<()> {
+    conn.prepare(sql)
+        .map(|_| ())
+        .map_err(|e| syn::Error::new(span, format!("invalid statement `{}`: {}", sql, e)))
+}
+
+// Ошибки проверки, которые должны прерывать компиляцию:
+// cargo test -p batiskaf_derive --features check --doc
+#[cfg(all(doctest, feature = "check"))]
+mod compile_fail {
+    //! Корректные структура и запрос компилируются:
+    //!
+    //! ```
+    //! use batiskaf_derive::{check_sql, SqlInsert};
+    //!
+    //! #[derive(SqlInsert)]
+    //! #[batiskaf(schema = "tests/schema.sql", table = "person")]
+    //! struct Person {
+    //!     #[batiskaf(primary_key, autogenerated)]
+    //!     id: i64,
+    //!     full_name: String,
+    //! }
+    //!
+    //! let sql = check_sql!("tests/schema.sql", "select id, full_name from person");
+    //! ```
+    //!
+    //! Столбца нет в таблице:
+    //!
+    //! ```compile_fail
+    //! use batiskaf_derive::SqlInsert;
+    //!
+    //! #[derive(SqlInsert)]
+    //! #[batiskaf(schema = "tests/schema.sql", table = "person")]
+    //! struct Person {
+    //!     #[batiskaf(primary_key, autogenerated)]
+    //!     id: i64,
+    //!     email: String,
+    //! }
+    //! ```
+    //!
+    //! Таблицы нет в схеме:
+    //!
+    //! ```compile_fail
+    //! use batiskaf_derive::SqlInsert;
+    //!
+    //! #[derive(SqlInsert)]
+    //! #[batiskaf(schema = "tests/schema.sql", table = "people")]
+    //! struct Person {
+    //!     #[batiskaf(primary_key, autogenerated)]
+    //!     id: i64,
+    //!     full_name: String,
+    //! }
+    //! ```
+    //!
+    //! Синтаксическая ошибка в запросе:
+    //!
+    //! ```compile_fail
+    //! use batiskaf_derive::check_sql;
+    //!
+    //! let sql = check_sql!("tests/schema.sql", "select id, full_name form person");
+    //! ```
+    //!
+    //! Неизвестный столбец в запросе:
+    //!
+    //! ```compile_fail
+    //! use batiskaf_derive::check_sql;
+    //!
+    //! let sql = check_sql!("tests/schema.sql", "select id, email from person");
+    //! ```
+}
@@ -1,5 +1,11 @@
 use std::collections::{HashMap, HashSet};
-use syn::{self, Attribute, Data, Field, Fields, Lit, Meta, NestedMeta};
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{
+    self, Attribute, AttributeArgs, Data, Expr, Field, Fields, Ident, Lit, Meta, NestedMeta, Path,
+};
 
 #[derive(Debug, Default)]
 pub(crate) struct Attributes {
@@ -25,11 +31,54 @@ impl Column {
     pub fn autogenerated(&self) -> bool {
         self.attrs.word.contains("autogenerated")
     }
+    // поля с атрибутами rest и has_many заполняются только в SqlResult
+    // и пропускаются остальными трейтами
     pub fn skip(&self) -> bool {
-        self.attrs.word.contains("skip")
+        self.attrs.word.contains("skip") || self.rest() || self.has_many().is_some()
+    }
+    pub fn rest(&self) -> bool {
+        self.attrs.word.contains("rest")
+    }
+    // Тип дочерних записей связи "один ко многим"
+    pub fn has_many(&self) -> Option<&String> {
+        self.attrs.name_value.get("has_many")
+    }
+    pub fn expand(&self) -> bool {
+        self.attrs.word.contains("expand")
     }
     pub fn default(&self) -> bool {
         self.attrs.word.contains("default")
+            || self.attrs.name_value.contains_key("default")
+            || self.attrs.name_value.contains_key("default_value")
+    }
+    // Синонимы имени столбца из атрибута alias = "name1, name2"
+    pub fn aliases(&self) -> Vec<String> {
+        match self.attrs.name_value.get("alias") {
+            Some(aliases) => aliases
+                .split(',')
+                .map(|alias| alias.trim().to_string())
+                .filter(|alias| !alias.is_empty())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+    pub fn null_as_default(&self) -> bool {
+        self.attrs.word.contains("null_as_default")
+    }
+    pub fn json(&self) -> bool {
+        self.attrs.word.contains("json")
+    }
+    // Функция преобразования from_sql или to_sql: задаётся одноимённым атрибутом
+    // или модулем из атрибута with
+    pub fn conversion(&self, kind: &str) -> Option<String> {
+        match self.attrs.name_value.get(kind) {
+            Some(path) => Some(path.clone()),
+            None => self
+                .attrs
+                .name_value
+                .get("with")
+                .map(|module| format!("{}::{}", module, kind)),
+        }
     }
 }
 
@@ -74,8 +123,134 @@ pub(crate) fn columns_with_fields<'a>(data: &'a Data, default: bool) -> Vec<(Col
 }
 
 pub(crate) fn parse_attributes(attrs: &[Attribute]) -> Attributes {
+    meta_to_attributes(attributes_to_meta(attrs))
+}
+
+// Аргументы атрибута-макроса, например #[mapper(crate = "...")]
+pub(crate) fn parse_attribute_args(args: AttributeArgs) -> Attributes {
+    meta_to_attributes(
+        args.into_iter()
+            .filter_map(|m| match m {
+                NestedMeta::Meta(meta) => Some(meta),
+                _ => None,
+            })
+            .collect(),
+    )
+}
+
+// Путь к крейту batiskaf в сгенерированном коде: `::batiskaf`
+// или значение атрибута `crate`, если batiskaf переименован или реэкспортирован
+pub(crate) fn crate_path(attrs: &Attributes) -> syn::Result<TokenStream> {
+    match attrs.name_value.get("crate") {
+        Some(path) => match syn::parse_str::<Path>(path) {
+            Ok(path) => Ok(quote!(#path)),
+            Err(_) => Err(syn::Error::new(
+                Span::call_site(),
+                format!("invalid crate path `{}`", path),
+            )),
+        },
+        None => Ok(quote!(::batiskaf)),
+    }
+}
+
+// Функция преобразования from_sql или to_sql для поля: атрибут json, способ хранения
+// (timestamp, uuid, decimal) или путь из атрибутов from_sql, to_sql, with
+pub(crate) fn conversion(
+    column: &Column,
+    field: &Field,
+    kind: &str,
+    krate: &TokenStream,
+) -> syn::Result<Option<TokenStream>> {
+    if column.json() {
+        return Ok(Some(if kind == "from_sql" {
+            let name = column.name();
+            quote!(|value| #krate::json::from_sql(#name, value))
+        } else {
+            quote!(#krate::json::to_sql)
+        }));
+    }
+    if let Some((strategy, _)) = storage(column, field)? {
+        let strategy = Ident::new(strategy, Span::call_site());
+        let kind = Ident::new(kind, Span::call_site());
+        return Ok(Some(
+            quote!(<#krate::storage::#strategy as #krate::storage::Strategy<_>>::#kind),
+        ));
+    }
+    match column.conversion(kind) {
+        Some(path) => match syn::parse_str::<Path>(&path) {
+            Ok(path) => Ok(Some(quote!(#path))),
+            Err(_) => Err(syn::Error::new(
+                field.span(),
+                format!("invalid {} function path `{}`", kind, path),
+            )),
+        },
+        None => Ok(None),
+    }
+}
+
+// Значение поля по-умолчанию: вызов функции из атрибута default = "path",
+// выражение из атрибута default_value = "..." или Default::default()
+pub(crate) fn default_value(column: &Column, field: &Field) -> syn::Result<TokenStream> {
+    let attrs = &column.attrs.name_value;
+    match (attrs.get("default"), attrs.get("default_value")) {
+        (Some(_), Some(_)) => Err(syn::Error::new(
+            field.span(),
+            "field can not have both `default = \"...\"` and `default_value` attributes",
+        )),
+        (Some(path), None) => match syn::parse_str::<Path>(path) {
+            Ok(path) => Ok(quote!(#path())),
+            Err(_) => Err(syn::Error::new(
+                field.span(),
+                format!(
+                    "invalid default function path `{}`, column DEFAULT is set by `sql_default`",
+                    path
+                ),
+            )),
+        },
+        (None, Some(value)) => match syn::parse_str::<Expr>(value) {
+            Ok(value) => Ok(quote!(#value)),
+            Err(_) => Err(syn::Error::new(
+                field.span(),
+                format!("invalid default value `{}`", value),
+            )),
+        },
+        (None, None) => Ok(quote!(::std::default::Default::default())),
+    }
+}
+
+// Способ хранения поля, заданный атрибутом timestamp, uuid или decimal:
+// тип из модуля batiskaf::storage и тип столбца для SqlSchema
+pub(crate) fn storage(
+    column: &Column,
+    field: &Field,
+) -> syn::Result<Option<(&'static str, &'static str)>> {
+    for attr in &["timestamp", "uuid", "decimal"] {
+        let value = match column.attrs.name_value.get(*attr) {
+            Some(value) => value,
+            None => continue,
+        };
+        let strategy = match (*attr, value.as_str()) {
+            ("timestamp", "unix") => ("Unix", "INTEGER"),
+            ("timestamp", "unix_ms") => ("UnixMs", "INTEGER"),
+            ("timestamp", "rfc3339") => ("Rfc3339", "TEXT"),
+            ("uuid", "blob") => ("Blob", "BLOB"),
+            ("uuid", "text") => ("Text", "TEXT"),
+            ("decimal", "text") => ("Text", "TEXT"),
+            ("decimal", "cents") => ("Cents", "INTEGER"),
+            _ => {
+                return Err(syn::Error::new(
+                    field.span(),
+                    format!("unsupported {} storage `{}`", attr, value),
+                ))
+            }
+        };
+        return Ok(Some(strategy));
+    }
+    Ok(None)
+}
+
+fn meta_to_attributes(meta: Vec<Meta>) -> Attributes {
     let mut a = Attributes::default();
-    let meta: Vec<Meta> = attributes_to_meta(attrs);
     for m in meta {
         match m {
             Meta::Word(ident) => {
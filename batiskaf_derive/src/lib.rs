@@ -6,23 +6,60 @@
 - autogenerated - поле является автогенерируемым и пропускается в SqlInsert
 - skip - поле пропускается
 - default - если поле пропущено или его нет в строке (SqlResult), использовать значение по-умолчанию
+- default = "path" - то же, значение по-умолчанию - результат вызова функции path()
+- default_value = "..." - то же, значение по-умолчанию - выражение Rust
+- null_as_default - если значение столбца NULL (SqlResult), использовать значение по-умолчанию
+- rest - поле-коллекция (String, Value) получает все столбцы, не соответствующие другим полям (SqlResult);
+  остальные derive-макросы пропускают поле
+- from_sql = "path" - функция fn(ValueRef) -> FromSqlResult<T> для чтения поля (SqlResult) вместо FromSql
+- to_sql = "path" - функция fn(&T) -> rusqlite::Result<ToSqlOutput> для передачи поля в запрос (SqlParam) вместо ToSql
+- with = "module" - то же, что from_sql = "module::from_sql", to_sql = "module::to_sql"
+- json - поле хранится как JSON-текст (serde); требует feature json крейта batiskaf
+- timestamp = "unix" | "unix_ms" | "rfc3339", uuid = "blob" | "text", decimal = "text" | "cents" -
+  способ хранения типов chrono, time, uuid и rust_decimal (batiskaf::storage);
+  требует одноимённой feature крейта batiskaf
 
+атрибут структуры для всех derive-макросов:
+- crate = "path" - путь к крейту batiskaf в сгенерированном коде (по-умолчанию ::batiskaf);
+  rusqlite используется через реэкспорт path::rusqlite
 
 SqlParam
 применяется только к именованным структурам
 атрибуты полей:
 - column
 - skip
+- to_sql, with - тип поля не должен зависеть от generic-параметров структуры
+- json
+- timestamp, uuid, decimal
+- expand - поле-коллекция: `:name` в запросе заменяется списком `:name_0, :name_1, ...` (expand_sql),
+  элементы передаются как отдельные параметры (expanded_params)
 
 SqlResult
 применяется только к именованным структурам
-все generic-типы в объявлении структуры получают дополнительные ограничения: Default + FromSql
+реализует from_row (поиск столбцов по имени), column_indices и from_row_indexed (чтение по индексам)
+все generic-типы в объявлении структуры получают дополнительное ограничение FromSql;
+типы полей, значение по-умолчанию которых - Default::default() (default, null_as_default),
+получают ограничение Default
 атрибут структуры:
-- default - все поля получают атрибут default
+- default - все поля без default = "..." и default_value = "..." получают атрибут default
+- null_as_default - все поля получают атрибут null_as_default
+- case_insensitive - имена столбцов сравниваются без учёта регистра любых символов (не только ASCII)
+- deny_unknown_columns - столбцы результата, которым не соответствует ни одно поле, - ошибка
+  (check_columns и from_row)
 атрибуты полей:
 - column
+- alias = "name1, name2" - синонимы имени столбца
 - skip
-- default
+- default, default = "path", default_value = "..."
+- null_as_default
+- rest - поле-коллекция со значениями столбцов, которым не соответствует ни одно поле
+- has_many = "Child", foreign_key = "column" - поле Vec<Child> с дочерними записями, внешний ключ
+  которых равен единственному полю primary_key; реализуется трейт HasMany<Child>,
+  записи загружаются функцией BatiskafConnection::load_has_many; остальные derive-макросы пропускают поле
+- order_by = "..." - порядок дочерних записей поля has_many (выражение ORDER BY, по-умолчанию rowid)
+- from_sql, with
+- json
+- timestamp, uuid, decimal
 
 SqlInsert
 атрибуты полей:
@@ -42,17 +79,68 @@ SqlDelete
 - primary_key
 - skip
 
+SqlSchema
+атрибуты полей:
+- column
+- primary_key - несколько полей образуют составной первичный ключ
+- autogenerated - только для единственного целочисленного первичного ключа (AUTOINCREMENT)
+- skip
+- unique
+- sql_default = "..." - значение столбца по-умолчанию (DEFAULT ...)
+- check = "..." - ограничение (CHECK (...))
+- sql_type = "..." - тип столбца; по-умолчанию определяется по типу поля:
+  целые числа и bool - INTEGER, f32 и f64 - REAL, String - TEXT, Vec<u8> - BLOB,
+  Option<T> - столбец допускает NULL, остальные столбцы - NOT NULL
+- json - тип столбца TEXT, если не задан sql_type
+- timestamp, uuid, decimal - тип столбца соответствует способу хранения, если не задан sql_type
+
+mapper
+атрибут трейта; методы трейта помечаются атрибутами с SQL-запросом:
+- select("...") - возвращает rusqlite::Result<T>, rusqlite::Result<Option<T>> или rusqlite::Result<Vec<T>>, T: SqlResult
+- insert("..."), update("..."), delete("...") - возвращают rusqlite::Result<usize>
+аргумент атрибута crate = "path" - как у derive-макросов
+аргумент, имя которого встречается в запросе как :name, передаётся как параметр (ToSql),
+остальные аргументы должны реализовывать SqlParam; их параметры-коллекции (expand) раскрываются в запросе
+
+include_queries!("path/to/file.sql")
+путь указывается относительно каталога крейта (CARGO_MANIFEST_DIR);
+файл состоит из блоков, для каждого блока генерируется функция fn name(conn: &Connection, params...) -> rusqlite::Result<returns>:
+-- name: find_person_by_id
+-- params: id: i64
+-- returns: Option<Person>
+select id, name, age from person where id = :id
+params и returns необязательны; returns по-умолчанию - usize (количество изменённых строк)
+
+include_migrations!("path/to/dir")
+список миграций &[(&str, &str)] для batiskaf::migrate::Migrator из файлов *.sql каталога
+(относительно CARGO_MANIFEST_DIR) в порядке возрастания имён файлов;
+название миграции - имя файла без расширения
+
+check (feature)
+атрибуты структуры для SqlInsert, SqlUpdate и SqlDelete:
+- schema = "schema.sql" - файл со схемой БД (относительно CARGO_MANIFEST_DIR)
+- table = "person" - таблица, в которой должны быть все столбцы структуры
+сгенерированный запрос должен успешно компилироваться (prepare) на созданной по схеме БД в памяти
+check_sql!("schema.sql", "select ...") - проверяет запрос и возвращает его как строковый литерал
+без feature check атрибуты schema и table игнорируются, а check_sql! только возвращает запрос
+
 */
 
 extern crate proc_macro;
 use proc_macro::TokenStream;
-use syn::{self, parse_macro_input, DeriveInput};
+use syn::punctuated::Punctuated;
+use syn::{self, parse_macro_input, AttributeArgs, DeriveInput, ItemTrait, LitStr, Token};
 
+mod check;
 mod column;
+mod mapper;
+mod migrations;
+mod queries;
 mod sql_delete;
 mod sql_insert;
 mod sql_param;
 mod sql_result;
+mod sql_schema;
 mod sql_update;
 
 #[proc_macro_derive(SqlParam, attributes(batiskaf))]
@@ -84,3 +172,55 @@ pub fn derive_sql_delete(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     sql_delete::derive(input).into()
 }
+
+#[proc_macro_derive(SqlSchema, attributes(batiskaf))]
+pub fn derive_sql_schema(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    sql_schema::derive(input).into()
+}
+
+#[proc_macro_attribute]
+pub fn mapper(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as AttributeArgs);
+    let input = parse_macro_input!(input as ItemTrait);
+    let krate = match column::crate_path(&column::parse_attribute_args(args)) {
+        Ok(krate) => krate,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    mapper::expand(input, &krate).into()
+}
+
+#[proc_macro]
+pub fn include_queries(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as LitStr);
+    queries::expand(input).into()
+}
+
+#[proc_macro]
+pub fn check_sql(input: TokenStream) -> TokenStream {
+    let parser = Punctuated::<LitStr, Token![,]>::parse_terminated;
+    let args = match syn::parse::Parser::parse(parser, input) {
+        Ok(args) => args,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let args: Vec<&LitStr> = args.iter().collect();
+    if args.len() != 2 {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            "expected schema path and SQL statement: `check_sql!(\"schema.sql\", \"select ...\")`",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let sql = args[1];
+    match check::check_statement(&args[0].value(), &sql.value(), sql.span()) {
+        Ok(()) => quote::quote!(#sql).into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+#[proc_macro]
+pub fn include_migrations(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as LitStr);
+    migrations::expand(input).into()
+}
@@ -0,0 +1,339 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{
+    self, Attribute, FnArg, GenericArgument, Ident, ItemTrait, Lit, Meta, NestedMeta, Pat,
+    PathArguments, ReturnType, TraitItem, TraitItemMethod, Type,
+};
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Select,
+    Insert,
+    Update,
+    Delete,
+}
+
+impl Kind {
+    fn from_name(name: &str) -> Option<Kind> {
+        match name {
+            "select" => Some(Kind::Select),
+            "insert" => Some(Kind::Insert),
+            "update" => Some(Kind::Update),
+            "delete" => Some(Kind::Delete),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn expand(mut input: ItemTrait, krate: &TokenStream) -> TokenStream {
+    let mut methods = Vec::new();
+    for item in input.items.iter_mut() {
+        if let TraitItem::Method(ref mut method) = *item {
+            match take_statement(&mut method.attrs) {
+                Ok(Some((kind, sql))) => methods.push(method_impl(method, kind, &sql, krate)),
+                Ok(None) if method.default.is_some() => (),
+                Ok(None) => {
+                    return syn::Error::new(
+                        method.sig.ident.span(),
+                        "mapper method must have `select`, `insert`, `update` or `delete` attribute",
+                    )
+                    .to_compile_error();
+                }
+                Err(e) => return e.to_compile_error(),
+            }
+        }
+    }
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    quote! {
+        #input
+
+        impl #impl_generics #name #ty_generics for #krate::rusqlite::Connection #where_clause {
+            #(#methods)*
+        }
+    }
+}
+
+// Извлекает из атрибутов метода SQL-запрос и удаляет соответствующий атрибут
+fn take_statement(attrs: &mut Vec<Attribute>) -> syn::Result<Option<(Kind, String)>> {
+    let mut result = None;
+    let mut rest = Vec::new();
+    for attr in attrs.drain(..) {
+        let kind = if attr.path.segments.len() == 1 {
+            Kind::from_name(&attr.path.segments[0].ident.to_string())
+        } else {
+            None
+        };
+        match kind {
+            Some(kind) => {
+                if result.is_some() {
+                    return Err(syn::Error::new(
+                        attr.span(),
+                        "mapper method must have exactly one SQL attribute",
+                    ));
+                }
+                result = Some((kind, sql_literal(&attr)?));
+            }
+            None => rest.push(attr),
+        }
+    }
+    *attrs = rest;
+    Ok(result)
+}
+
+fn sql_literal(attr: &Attribute) -> syn::Result<String> {
+    if let Ok(Meta::List(list)) = attr.parse_meta() {
+        if list.nested.len() == 1 {
+            if let Some(NestedMeta::Literal(Lit::Str(lit))) =
+                list.nested.first().map(|p| *p.value())
+            {
+                return Ok(lit.value());
+            }
+        }
+    }
+    Err(syn::Error::new(
+        attr.span(),
+        "expected SQL string literal, e.g. `#[select(\"select ...\")]`",
+    ))
+}
+
+pub(crate) enum Output<'a> {
+    One(&'a Type),
+    Optional(&'a Type),
+    Many(&'a Type),
+    Changes,
+}
+
+fn method_impl(
+    method: &TraitItemMethod,
+    kind: Kind,
+    sql: &str,
+    krate: &TokenStream,
+) -> TokenStream {
+    let sig = &method.sig;
+    let output = match output(&sig.decl.output) {
+        Some(output) => output,
+        None => {
+            return syn::Error::new(
+                sig.decl.output.span(),
+                "mapper method must return `rusqlite::Result<_>`",
+            )
+            .to_compile_error();
+        }
+    };
+    let mut has_receiver = false;
+    let mut args = Vec::new();
+    for arg in sig.decl.inputs.iter() {
+        match *arg {
+            FnArg::SelfRef(ref r) if r.mutability.is_none() => has_receiver = true,
+            FnArg::Captured(ref arg) => {
+                let ident = match arg.pat {
+                    Pat::Ident(ref p) => &p.ident,
+                    _ => {
+                        return syn::Error::new(
+                            arg.pat.span(),
+                            "mapper method argument must be a plain identifier",
+                        )
+                        .to_compile_error();
+                    }
+                };
+                if is_reserved(ident) {
+                    return syn::Error::new(
+                        ident.span(),
+                        "mapper method argument names starting with `__batiskaf_` are reserved",
+                    )
+                    .to_compile_error();
+                }
+                args.push(ident);
+            }
+            _ => {
+                return syn::Error::new(arg.span(), "unsupported mapper method argument")
+                    .to_compile_error();
+            }
+        }
+    }
+    if !has_receiver {
+        return syn::Error::new(sig.ident.span(), "mapper method must take `&self`")
+            .to_compile_error();
+    }
+    let valid = match (kind, &output) {
+        (Kind::Select, Output::Changes) => {
+            Err("`select` method must return `T`, `Option<T>` or `Vec<T>`")
+        }
+        (Kind::Select, _) | (_, Output::Changes) => Ok(()),
+        _ => Err("`insert`, `update` and `delete` methods must return `rusqlite::Result<usize>`"),
+    };
+    if let Err(message) = valid {
+        return syn::Error::new(sig.decl.output.span(), message).to_compile_error();
+    }
+    let body = statement(&quote!(self), sql, &args, &output, krate);
+    quote! {
+        #sig {
+            #body
+        }
+    }
+}
+
+// Имена с префиксом `__batiskaf_` заняты локальными переменными сгенерированного кода
+pub(crate) fn is_reserved(ident: &Ident) -> bool {
+    ident.to_string().starts_with("__batiskaf_")
+}
+
+// Тело функции, выполняющей запрос `sql` на соединении `conn`; локальные переменные
+// имеют префикс `__batiskaf_`, чтобы не совпадать с именами аргументов
+pub(crate) fn statement(
+    conn: &TokenStream,
+    sql: &str,
+    args: &[&Ident],
+    output: &Output,
+    krate: &TokenStream,
+) -> TokenStream {
+    let sql_params = parameters(sql);
+    let binds = args.iter().map(|ident| bind(ident, &sql_params, krate));
+    // параметры-коллекции структур SqlParam раскрываются в SQL-коде до подготовки запроса
+    let expands = args
+        .iter()
+        .filter(|ident| !sql_params.contains(&ident.to_string()))
+        .map(|ident| quote!(let __batiskaf_sql = #ident.expand_sql(__batiskaf_sql);));
+    let execute = match *output {
+        Output::One(ty) => quote! {
+            let __batiskaf_row = #krate::select_iter::<#ty>(&mut __batiskaf_stmt, &__batiskaf_params)?.next();
+            __batiskaf_row.unwrap_or(Err(#krate::rusqlite::Error::QueryReturnedNoRows))
+        },
+        Output::Optional(ty) => quote! {
+            let __batiskaf_row = #krate::select_iter::<#ty>(&mut __batiskaf_stmt, &__batiskaf_params)?.next();
+            __batiskaf_row.transpose()
+        },
+        Output::Many(ty) => quote! {
+            let __batiskaf_result = #krate::select_iter::<#ty>(&mut __batiskaf_stmt, &__batiskaf_params)?.collect();
+            __batiskaf_result
+        },
+        Output::Changes => quote! {
+            #krate::backend::execute(&mut __batiskaf_stmt, &__batiskaf_params)
+        },
+    };
+    quote! {
+        #[allow(unused_imports)]
+        use #krate::SqlParam as _;
+        let __batiskaf_sql = ::std::borrow::Cow::Borrowed(#sql);
+        #(#expands)*
+        let mut __batiskaf_stmt = #conn.prepare(&__batiskaf_sql)?;
+        #[allow(unused_mut)]
+        let mut __batiskaf_params: ::std::vec::Vec<(&str, &dyn #krate::rusqlite::types::ToSql)> =
+            ::std::vec::Vec::new();
+        #(#binds)*
+        #execute
+    }
+}
+
+// Аргумент, имя которого встречается в запросе, передаётся как параметр;
+// иначе аргумент считается структурой, реализующей SqlParam
+fn bind(ident: &Ident, sql_params: &[String], krate: &TokenStream) -> TokenStream {
+    let name = ident.to_string();
+    if sql_params.contains(&name) {
+        let param = format!(":{}", name);
+        quote! {
+            __batiskaf_params.push((#param, &#ident as &dyn #krate::rusqlite::types::ToSql));
+        }
+    } else {
+        let expanded = Ident::new(&format!("__batiskaf_{}_expanded", name), ident.span());
+        quote! {
+            let #expanded = #ident.expanded_params();
+            __batiskaf_params.extend(#ident.to_named_params(&__batiskaf_stmt));
+            __batiskaf_params.extend(#krate::convert::expanded_named_params(&__batiskaf_stmt, &#expanded));
+        }
+    }
+}
+
+fn output(output: &ReturnType) -> Option<Output<'_>> {
+    match *output {
+        ReturnType::Type(_, ref ty) => generic_argument(ty, "Result").map(output_type),
+        ReturnType::Default => None,
+    }
+}
+
+pub(crate) fn output_type(ty: &Type) -> Output<'_> {
+    if let Some(inner) = generic_argument(ty, "Vec") {
+        Output::Many(inner)
+    } else if let Some(inner) = generic_argument(ty, "Option") {
+        Output::Optional(inner)
+    } else if is_usize(ty) {
+        Output::Changes
+    } else {
+        Output::One(ty)
+    }
+}
+
+pub(crate) fn generic_argument<'a>(ty: &'a Type, name: &str) -> Option<&'a Type> {
+    if let Type::Path(ref ty) = *ty {
+        let segment = ty.path.segments.last()?.into_value();
+        if segment.ident != name {
+            return None;
+        }
+        if let PathArguments::AngleBracketed(ref args) = segment.arguments {
+            if let Some(GenericArgument::Type(ref ty)) = args.args.first().map(|p| *p.value()) {
+                return Some(ty);
+            }
+        }
+    }
+    None
+}
+
+fn is_usize(ty: &Type) -> bool {
+    match *ty {
+        Type::Path(ref ty) => ty.qself.is_none() && ty.path.is_ident("usize"),
+        _ => false,
+    }
+}
+
+// Имена именованных параметров (`:name`) в SQL-запросе без префикса
+pub(crate) fn parameters(sql: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' | '"' | '`' => {
+                for x in chars.by_ref() {
+                    if x == c {
+                        break;
+                    }
+                }
+            }
+            // комментарии `-- ...` и `/* ... */` пропускаются
+            '-' if chars.peek() == Some(&'-') => {
+                for x in chars.by_ref() {
+                    if x == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut star = false;
+                for x in chars.by_ref() {
+                    if star && x == '/' {
+                        break;
+                    }
+                    star = x == '*';
+                }
+            }
+            ':' => {
+                let mut name = String::new();
+                while let Some(&x) = chars.peek() {
+                    if x.is_alphanumeric() || x == '_' {
+                        name.push(x);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                if !name.is_empty() && !result.contains(&name) {
+                    result.push(name);
+                }
+            }
+            _ => (),
+        }
+    }
+    result
+}
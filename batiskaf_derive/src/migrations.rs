@@ -0,0 +1,51 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{self, LitStr};
+
+// Список миграций из каталога: файлы *.sql в порядке возрастания имён,
+// название миграции - имя файла без расширения
+pub(crate) fn expand(path: LitStr) -> TokenStream {
+    let mut dir = match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::new(),
+    };
+    dir.push(path.value());
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            return syn::Error::new(
+                path.span(),
+                format!("cannot read directory `{}`: {}", dir.display(), e),
+            )
+            .to_compile_error();
+        }
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().is_some_and(|x| x == "sql"))
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        return syn::Error::new(
+            path.span(),
+            format!("no migrations found in `{}`", dir.display()),
+        )
+        .to_compile_error();
+    }
+    let migrations = files.iter().map(|file| {
+        let name = file
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let file = file.to_string_lossy().into_owned();
+        quote! { (#name, include_str!(#file)) }
+    });
+    quote! {
+        &[#(#migrations),*]
+    }
+}
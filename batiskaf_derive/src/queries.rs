@@ -0,0 +1,218 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{self, FnArg, Ident, LitStr, Pat, ReturnType, TraitItemMethod};
+
+use crate::mapper::{generic_argument, is_reserved, output_type, statement};
+
+struct Query {
+    line: usize,
+    name: String,
+    params_line: usize,
+    params: String,
+    returns: String,
+    sql: String,
+}
+
+pub(crate) fn expand(path: LitStr) -> TokenStream {
+    let span = path.span();
+    let mut full_path = match env::var_os("CARGO_MANIFEST_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::new(),
+    };
+    full_path.push(path.value());
+    let text = match fs::read_to_string(&full_path) {
+        Ok(text) => text,
+        Err(e) => {
+            return syn::Error::new(
+                span,
+                format!("cannot read `{}`: {}", full_path.display(), e),
+            )
+            .to_compile_error();
+        }
+    };
+    let queries = match parse(&text) {
+        Ok(queries) => queries,
+        Err((line, message)) => {
+            return syn::Error::new(span, format!("{}:{}: {}", path.value(), line, message))
+                .to_compile_error();
+        }
+    };
+    let mut functions = Vec::new();
+    for query in queries.iter() {
+        match function(query) {
+            Ok(f) => functions.push(f),
+            Err((line, message)) => {
+                return syn::Error::new(span, format!("{}:{}: {}", path.value(), line, message))
+                    .to_compile_error();
+            }
+        }
+    }
+    // include_str! заставляет компилятор пересобрать крейт при изменении файла
+    let full_path = full_path.to_string_lossy().into_owned();
+    quote! {
+        const _: &str = include_str!(#full_path);
+        #(#functions)*
+    }
+}
+
+// Разбирает файл на именованные блоки:
+// -- name: find_person_by_id
+// -- params: id: i64
+// -- returns: Option<Person>
+// select id, name, age from person where id = :id
+fn parse(text: &str) -> Result<Vec<Query>, (usize, String)> {
+    let mut queries: Vec<Query> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let trimmed = line.trim();
+        if let Some(name) = directive(trimmed, "name") {
+            if syn::parse_str::<Ident>(name).is_err() {
+                return Err((line_number, format!("invalid query name `{}`", name)));
+            }
+            if queries.iter().any(|q| q.name == name) {
+                return Err((line_number, format!("duplicate query name `{}`", name)));
+            }
+            queries.push(Query {
+                line: line_number,
+                name: name.to_string(),
+                params_line: line_number,
+                params: String::new(),
+                returns: String::new(),
+                sql: String::new(),
+            });
+            continue;
+        }
+        let query = match queries.last_mut() {
+            Some(query) => query,
+            None if trimmed.is_empty() || trimmed.starts_with("--") => continue,
+            None => {
+                return Err((
+                    line_number,
+                    "SQL outside of a named block, expected `-- name: ...`".to_string(),
+                ));
+            }
+        };
+        if query.sql.is_empty() {
+            if let Some(params) = directive(trimmed, "params") {
+                query.params_line = line_number;
+                query.params = params.to_string();
+                continue;
+            }
+            if let Some(returns) = directive(trimmed, "returns") {
+                query.returns = returns.to_string();
+                continue;
+            }
+            if trimmed.is_empty() {
+                continue;
+            }
+        }
+        query.sql.push_str(line);
+        query.sql.push('\n');
+    }
+    for query in queries.iter_mut() {
+        query.sql = query.sql.trim().to_string();
+        if query.sql.is_empty() {
+            return Err((query.line, format!("query `{}` is empty", query.name)));
+        }
+    }
+    Ok(queries)
+}
+
+fn directive<'a>(line: &'a str, name: &str) -> Option<&'a str> {
+    let rest = line
+        .strip_prefix("--")?
+        .trim_start_matches('-')
+        .trim_start();
+    let rest = rest.strip_prefix(name)?.trim_start();
+    rest.strip_prefix(':').map(str::trim)
+}
+
+fn function(query: &Query) -> Result<TokenStream, (usize, String)> {
+    let returns = if query.returns.is_empty() {
+        "usize"
+    } else {
+        &query.returns
+    };
+    let sig = format!(
+        "fn {}({}) -> ::batiskaf::rusqlite::Result<{}>;",
+        query.name, query.params, returns
+    );
+    let method: TraitItemMethod = syn::parse_str(&sig).map_err(|e| {
+        (
+            query.line,
+            format!("invalid signature of query `{}`: {}", query.name, e),
+        )
+    })?;
+    let mut args = Vec::new();
+    for arg in method.sig.decl.inputs.iter() {
+        match *arg {
+            FnArg::Captured(ref arg) => match arg.pat {
+                Pat::Ident(ref p) if is_reserved(&p.ident) => {
+                    return Err((
+                        query.params_line,
+                        format!(
+                            "query parameter name `{}` is reserved: names starting with \
+                             `__batiskaf_` are used by generated code",
+                            p.ident
+                        ),
+                    ));
+                }
+                Pat::Ident(ref p) => args.push(&p.ident),
+                _ => {
+                    return Err((
+                        query.params_line,
+                        "query parameter must be a plain identifier".to_string(),
+                    ));
+                }
+            },
+            _ => {
+                return Err((
+                    query.params_line,
+                    format!("invalid parameters `{}`", query.params),
+                ));
+            }
+        }
+    }
+    let ty = match method.sig.decl.output {
+        ReturnType::Type(_, ref ty) => generic_argument(ty, "Result"),
+        ReturnType::Default => None,
+    };
+    let output = match ty {
+        Some(ty) => output_type(ty),
+        None => return Err((query.line, format!("invalid return type `{}`", returns))),
+    };
+    let name = Ident::new(&query.name, Span::call_site());
+    let inputs = &method.sig.decl.inputs;
+    let result = &method.sig.decl.output;
+    // соединение названо так же, как локальные переменные тела, чтобы не совпадать
+    // с параметрами запроса
+    let body = statement(
+        &quote!(__batiskaf_conn),
+        &query.sql,
+        &args,
+        &output,
+        &quote!(::batiskaf),
+    );
+    Ok(quote! {
+        pub fn #name(__batiskaf_conn: &::batiskaf::rusqlite::Connection, #inputs) #result {
+            #body
+        }
+    })
+}
+
+// Ошибки разбора, которые должны прерывать компиляцию:
+// cargo test -p batiskaf_derive --doc
+#[cfg(doctest)]
+mod compile_fail {
+    //! Имя параметра с префиксом `__batiskaf_` зарезервировано:
+    //!
+    //! ```compile_fail
+    //! mod person {
+    //!     batiskaf_derive::include_queries!("tests/queries/reserved.sql");
+    //! }
+    //! ```
+}
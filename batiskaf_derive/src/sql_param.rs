@@ -3,40 +3,92 @@ use quote::{quote, quote_spanned};
 use syn::spanned::Spanned;
 use syn::{parse_quote, DeriveInput, Field, GenericParam, Generics};
 
-use crate::column::{columns_with_fields, Column};
+use crate::column::{columns_with_fields, conversion, crate_path, parse_attributes, Column};
 
 pub(crate) fn derive(input: DeriveInput) -> TokenStream {
     let name = input.ident;
-    let generics = add_trait_bounds(input.generics);
+    let krate = match crate_path(&parse_attributes(&input.attrs)) {
+        Ok(krate) => krate,
+        Err(e) => return e.to_compile_error(),
+    };
+    let generics = add_trait_bounds(input.generics, &krate);
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let cs = columns_with_fields(&input.data, false);
     let cs: Vec<(Column, &Field)> = cs.into_iter().filter(|cf| !cf.0.skip()).collect();
-    let tokens = cs.iter().map(|cf| {
-        let name = &cf.1.ident;
-        let param = &format!(":{}", cf.0.name());
-        quote_spanned! { cf.1.span() =>
-            if let Ok(Some(_)) = stmt.parameter_index(#param) {
-                params.push((#param, &self.#name as &dyn ::rusqlite::types::ToSql));
+    let mut tokens = Vec::new();
+    let mut expand_sql = Vec::new();
+    let mut expanded_params = Vec::new();
+    for (c, f) in cs.iter() {
+        let name = &f.ident;
+        let ty = &f.ty;
+        let param = &format!(":{}", c.name());
+        // параметр-коллекция: `:name` заменяется списком `:name_0, :name_1, ...`
+        if c.expand() {
+            if let Ok(Some(_)) = conversion(c, f, "to_sql", &krate) {
+                return syn::Error::new(
+                    f.ident.span(),
+                    "`expand` field cannot be converted with `to_sql`, `with` or `json`",
+                )
+                .to_compile_error();
             }
+            expand_sql.push(quote_spanned! { f.span() =>
+                let sql = #krate::convert::expand_parameter(sql, #param, self.#name.len());
+            });
+            expanded_params.push(quote_spanned! { f.span() =>
+                params.extend(#krate::convert::expanded_params(#param, &self.#name));
+            });
+            continue;
         }
-    });
+        let value = match conversion(c, f, "to_sql", &krate) {
+            Ok(Some(to_sql)) => quote_spanned! { f.span() =>
+                {
+                    enum ToSqlFn {}
+                    impl #krate::convert::ToSqlFn<#ty> for ToSqlFn {
+                        fn to_sql(value: &#ty) -> #krate::rusqlite::Result<#krate::rusqlite::types::ToSqlOutput<'_>> {
+                            #to_sql(value)
+                        }
+                    }
+                    #krate::convert::ToSqlWith::<ToSqlFn, #ty>::new(&self.#name)
+                }
+            },
+            Ok(None) => quote_spanned! { f.span() => &self.#name },
+            Err(e) => return e.to_compile_error(),
+        };
+        tokens.push(quote_spanned! { f.span() =>
+            if let Ok(Some(_)) = stmt.parameter_index(#param) {
+                params.push((#param, #value as &dyn #krate::rusqlite::types::ToSql));
+            }
+        });
+    }
     quote! {
-        impl #impl_generics ::batiskaf::SqlParam for #name #ty_generics #where_clause {
-            fn to_named_params(&self, stmt: &::rusqlite::Statement) -> ::std::vec::Vec<(&str, &dyn ::rusqlite::types::ToSql)> {
+        impl #impl_generics #krate::SqlParam for #name #ty_generics #where_clause {
+            fn to_named_params(&self, stmt: &#krate::rusqlite::Statement<'_>) -> ::std::vec::Vec<(&str, &dyn #krate::rusqlite::types::ToSql)> {
                 let mut params = ::std::vec::Vec::new();
                 #(#tokens)*
                 params
             }
+
+            fn expand_sql<'s>(&self, sql: ::std::borrow::Cow<'s, str>) -> ::std::borrow::Cow<'s, str> {
+                #(#expand_sql)*
+                sql
+            }
+
+            fn expanded_params(&self) -> ::std::vec::Vec<(::std::string::String, &dyn #krate::rusqlite::types::ToSql)> {
+                #[allow(unused_mut)]
+                let mut params = ::std::vec::Vec::new();
+                #(#expanded_params)*
+                params
+            }
         }
     }
 }
 
-fn add_trait_bounds(mut generics: Generics) -> Generics {
+fn add_trait_bounds(mut generics: Generics, krate: &TokenStream) -> Generics {
     for param in &mut generics.params {
         if let GenericParam::Type(ref mut type_param) = *param {
             type_param
                 .bounds
-                .push(parse_quote!(::rusqlite::types::ToSql));
+                .push(parse_quote!(#krate::rusqlite::types::ToSql));
         }
     }
     generics
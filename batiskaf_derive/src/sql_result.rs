@@ -1,18 +1,30 @@
 use proc_macro2::TokenStream;
 use quote::{quote, quote_spanned};
 use syn::spanned::Spanned;
-use syn::{parse_quote, DeriveInput, GenericParam, Generics};
+use syn::{parse_quote, DeriveInput, Field, GenericParam, Generics, Ident, Type};
 
-use crate::column::{columns_with_fields, parse_attributes};
+use crate::column::{
+    columns_with_fields, conversion, crate_path, default_value, parse_attributes, Column,
+};
 
 pub(crate) fn derive(input: DeriveInput) -> TokenStream {
     let name = input.ident;
-    let generics = add_trait_bounds(input.generics);
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let attrs = parse_attributes(&input.attrs);
-    let cs = columns_with_fields(&input.data, attrs.word.contains("default"));
+    let krate = match crate_path(&attrs) {
+        Ok(krate) => krate,
+        Err(e) => return e.to_compile_error(),
+    };
+    let mut cs = columns_with_fields(&input.data, attrs.word.contains("default"));
+    if attrs.word.contains("null_as_default") {
+        for (c, _) in cs.iter_mut() {
+            c.attrs.word.insert("null_as_default".to_string());
+        }
+    }
+    let case_insensitive = attrs.word.contains("case_insensitive");
+    let generics = add_trait_bounds(input.generics, &cs, &krate);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     for (c, f) in cs.iter() {
-        if c.skip() && !c.default() {
+        if c.skip() && !c.rest() && c.has_many().is_none() && !c.default() {
             return syn::Error::new(
                 f.ident.span(),
                 "field with `skip` attribute must have `default` attribute".to_string(),
@@ -20,50 +32,260 @@ pub(crate) fn derive(input: DeriveInput) -> TokenStream {
             .to_compile_error();
         }
     }
-    let tokens = cs.iter().map(|cf| {
-        let name = &cf.1.ident;
-        let param = cf.0.name();
-        if cf.0.skip() {
-            quote_spanned! { cf.1.span() =>
-                #name: ::std::default::Default::default()
+    let deny_unknown_columns = attrs.word.contains("deny_unknown_columns");
+    if let Some((_, f)) = cs.iter().find(|(c, _)| c.rest()) {
+        if deny_unknown_columns {
+            return syn::Error::new(
+                f.ident.span(),
+                "`rest` field conflicts with `deny_unknown_columns` attribute".to_string(),
+            )
+            .to_compile_error();
+        }
+    }
+    let mut tokens = Vec::new();
+    let mut indexed_tokens = Vec::new();
+    let mut indices = Vec::new();
+    let mut known = Vec::new();
+    for (i, (c, f)) in cs.iter().enumerate() {
+        let name = &f.ident;
+        let param = c.name();
+        if c.rest() {
+            indices.push(quote!(::std::option::Option::None));
+            tokens.push(quote_spanned! { f.span() =>
+                #name: #krate::convert::rest_columns::<Self, _>(row, None)?
+            });
+            indexed_tokens.push(quote_spanned! { f.span() =>
+                #name: #krate::convert::rest_columns::<Self, _>(row, Some(indices))?
+            });
+            continue;
+        }
+        // значение столбца `column` (имя или индекс)
+        let get = match conversion(c, f, "from_sql", &krate) {
+            Ok(Some(from_sql)) => quote_spanned! { f.span() =>
+                #krate::convert::get_with(row, column, #from_sql)
+            },
+            Ok(None) => quote_spanned! { f.span() => row.get(column) },
+            Err(e) => return e.to_compile_error(),
+        };
+        let default = match default_value(c, f) {
+            Ok(default) => default,
+            Err(e) => return e.to_compile_error(),
+        };
+        let get = if c.null_as_default() {
+            quote_spanned! { f.span() =>
+                #krate::convert::is_null(row, column)
+                    .and_then(|null| if null { Ok(#default) } else { #get })
             }
-        } else if cf.0.default() {
-            quote_spanned! { cf.1.span() =>
-                #name: {
-                    let x = row.get(#param);
-                    if let Err(::rusqlite::Error::InvalidColumnName(_)) = x {
-                        ::std::default::Default::default()
-                    } else {
-                        x?
-                    }
+        } else {
+            get
+        };
+        let aliases = &c.aliases();
+        let by_name = if aliases.is_empty() && !case_insensitive {
+            quote_spanned! { f.span() =>
+                {
+                    let column = #param;
+                    #get
                 }
             }
         } else {
-            quote_spanned! { cf.1.span() =>
-                #name: row.get(#param)?
+            quote_spanned! { f.span() =>
+                #krate::convert::row_column(row, &[#param, #(#aliases),*], #case_insensitive)
+                    .and_then(|column| #get)
             }
+        };
+        let by_index = quote_spanned! { f.span() =>
+            #krate::convert::column_at(indices, #i, #param).and_then(|column| #get)
+        };
+        if !c.skip() {
+            known.push(quote!(&[#param, #(#aliases),*]));
         }
-    });
+        indices.push(if c.skip() {
+            quote!(::std::option::Option::None)
+        } else {
+            quote!(#krate::convert::find_column(&columns, &[#param, #(#aliases),*], #case_insensitive))
+        });
+        tokens.push(field_value(c, f, name, &by_name, &default, &krate));
+        indexed_tokens.push(field_value(c, f, name, &by_index, &default, &krate));
+    }
+    // deny_unknown_columns: проверка столбцов запроса в check_columns и в from_row
+    let (check_row, check_columns) = if deny_unknown_columns {
+        (
+            quote!(#krate::convert::check_row_columns::<Self>(row)?;),
+            quote! {
+                fn check_columns(stmt: &#krate::rusqlite::Statement<'_>) -> #krate::rusqlite::Result<()> {
+                    #krate::convert::deny_unknown_columns(
+                        &stmt.column_names(),
+                        &[#(#known),*],
+                        #case_insensitive,
+                    )
+                }
+            },
+        )
+    } else {
+        (quote!(), quote!())
+    };
+    let has_many = match has_many(&name, &generics, &cs, &krate) {
+        Ok(has_many) => has_many,
+        Err(e) => return e.to_compile_error(),
+    };
     quote! {
-        impl #impl_generics ::batiskaf::SqlResult for #name #ty_generics #where_clause {
-            fn from_row(row: &::rusqlite::Row) -> rusqlite::Result<Self> {
+        #(#has_many)*
+
+        impl #impl_generics #krate::SqlResult for #name #ty_generics #where_clause {
+            fn from_row(row: &#krate::rusqlite::Row<'_>) -> #krate::rusqlite::Result<Self> {
+                #check_row
                 Ok(#name {
                     #(#tokens),*
                 })
             }
+
+            fn column_indices(stmt: &#krate::rusqlite::Statement<'_>) -> ::std::vec::Vec<::std::option::Option<usize>> {
+                let columns = stmt.column_names();
+                ::std::vec![#(#indices),*]
+            }
+
+            fn from_row_indexed(
+                row: &#krate::rusqlite::Row<'_>,
+                indices: &[::std::option::Option<usize>],
+            ) -> #krate::rusqlite::Result<Self> {
+                Ok(#name {
+                    #(#indexed_tokens),*
+                })
+            }
+
+            #check_columns
         }
     }
 }
 
-fn add_trait_bounds(mut generics: Generics) -> Generics {
+// Связи "один ко многим": поле has_many = "Child" получает дочерние записи, внешний ключ
+// foreign_key = "column" которых равен значению первичного ключа (поле primary_key);
+// order_by = "..." задаёт порядок дочерних записей
+fn has_many(
+    name: &Ident,
+    generics: &Generics,
+    cs: &[(Column, &Field)],
+    krate: &TokenStream,
+) -> syn::Result<Vec<TokenStream>> {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mut tokens = Vec::new();
+    for (c, f) in cs.iter() {
+        let child = match c.has_many() {
+            Some(child) => child,
+            None => continue,
+        };
+        let child: Type = syn::parse_str(child).map_err(|_| {
+            syn::Error::new(f.span(), format!("invalid `has_many` type `{}`", child))
+        })?;
+        let foreign_key = match c.attrs.name_value.get("foreign_key") {
+            Some(foreign_key) => foreign_key,
+            None => {
+                return Err(syn::Error::new(
+                    f.span(),
+                    "`has_many` field must have `foreign_key` attribute",
+                ))
+            }
+        };
+        let mut keys = cs.iter().filter(|(c, _)| c.primary_key());
+        let key = match (keys.next(), keys.next()) {
+            (Some((_, key)), None) => key,
+            _ => {
+                return Err(syn::Error::new(
+                    f.span(),
+                    "`has_many` requires exactly one `primary_key` field",
+                ))
+            }
+        };
+        let key_name = &key.ident;
+        let key_ty = &key.ty;
+        let field = &f.ident;
+        let order_by = c.attrs.name_value.get("order_by").map(|order_by| {
+            quote! {
+                fn order_by() -> &'static str {
+                    #order_by
+                }
+            }
+        });
+        tokens.push(quote_spanned! { f.span() =>
+            impl #impl_generics #krate::HasMany<#child> for #name #ty_generics #where_clause {
+                type Key = #key_ty;
+
+                fn foreign_key() -> &'static str {
+                    #foreign_key
+                }
+
+                #order_by
+
+                fn key(&self) -> &#key_ty {
+                    &self.#key_name
+                }
+
+                fn children_mut(&mut self) -> &mut ::std::vec::Vec<#child> {
+                    &mut self.#field
+                }
+            }
+        });
+    }
+    Ok(tokens)
+}
+
+fn field_value(
+    c: &Column,
+    f: &Field,
+    name: &Option<Ident>,
+    get: &TokenStream,
+    default: &TokenStream,
+    krate: &TokenStream,
+) -> TokenStream {
+    if c.skip() {
+        quote_spanned! { f.span() =>
+            #name: #default
+        }
+    } else if c.default() {
+        quote_spanned! { f.span() =>
+            #name: {
+                let x = #get;
+                if let Err(#krate::rusqlite::Error::InvalidColumnName(_)) = x {
+                    #default
+                } else {
+                    x?
+                }
+            }
+        }
+    } else {
+        quote_spanned! { f.span() =>
+            #name: #get?
+        }
+    }
+}
+
+// Поля, значение по-умолчанию которых - Default::default(), должны реализовывать Default
+// (атрибуты default и null_as_default без default = "..." и default_value = "...")
+fn add_trait_bounds(
+    mut generics: Generics,
+    cs: &[(Column, &Field)],
+    krate: &TokenStream,
+) -> Generics {
+    let mut generic = false;
     for param in &mut generics.params {
         if let GenericParam::Type(ref mut type_param) = *param {
             type_param
                 .bounds
-                .push(parse_quote!(::rusqlite::types::FromSql));
-            type_param
-                .bounds
-                .push(parse_quote!(::std::default::Default));
+                .push(parse_quote!(#krate::rusqlite::types::FromSql));
+            generic = true;
+        }
+    }
+    if generic {
+        for (c, f) in cs.iter() {
+            let explicit = c.attrs.name_value.contains_key("default")
+                || c.attrs.name_value.contains_key("default_value");
+            if (c.default() || c.null_as_default()) && !explicit {
+                let ty = &f.ty;
+                generics
+                    .make_where_clause()
+                    .predicates
+                    .push(parse_quote!(#ty: ::std::default::Default));
+            }
         }
     }
     generics
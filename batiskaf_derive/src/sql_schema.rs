@@ -0,0 +1,146 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{DeriveInput, GenericArgument, PathArguments, Type};
+
+use crate::column::{columns_with_fields, crate_path, parse_attributes, storage};
+
+pub(crate) fn derive(input: DeriveInput) -> TokenStream {
+    let name = &input.ident;
+    let krate = match crate_path(&parse_attributes(&input.attrs)) {
+        Ok(krate) => krate,
+        Err(e) => return e.to_compile_error(),
+    };
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let cs = columns_with_fields(&input.data, false);
+    let cs: Vec<_> = cs.into_iter().filter(|cf| !cf.0.skip()).collect();
+    let keys: Vec<String> = cs
+        .iter()
+        .filter(|cf| cf.0.primary_key())
+        .map(|cf| cf.0.name())
+        .collect();
+    let mut definitions = Vec::new();
+    let mut columns = Vec::new();
+    for (c, f) in cs.iter() {
+        let (affinity, nullable) = affinity(&f.ty);
+        let column = c.name();
+        let primary_key = c.primary_key();
+        columns.push(quote! {
+            #krate::ColumnDef {
+                name: #column.to_string(),
+                nullable: #nullable,
+                primary_key: #primary_key,
+            }
+        });
+        let storage = match storage(c, f) {
+            Ok(storage) => storage,
+            Err(e) => return e.to_compile_error(),
+        };
+        let affinity = match (c.attrs.name_value.get("sql_type"), storage) {
+            (Some(sql_type), _) => Some(sql_type.as_str()),
+            (None, _) if c.json() => Some("TEXT"),
+            (None, Some((_, affinity))) => Some(affinity),
+            (None, None) => affinity,
+        };
+        let integer = affinity.is_some_and(|a| a.eq_ignore_ascii_case("INTEGER"));
+        if c.autogenerated() && !(c.primary_key() && keys.len() == 1 && integer) {
+            return syn::Error::new(
+                f.ident.span(),
+                "`autogenerated` field must be the only integer `primary_key` field",
+            )
+            .to_compile_error();
+        }
+        let mut d = c.name();
+        if let Some(affinity) = affinity {
+            d.push(' ');
+            d.push_str(affinity);
+        }
+        let rowid = c.primary_key() && keys.len() == 1 && integer;
+        if c.primary_key() && keys.len() == 1 {
+            d.push_str(" PRIMARY KEY");
+            if c.autogenerated() {
+                d.push_str(" AUTOINCREMENT");
+            }
+        }
+        if !nullable && !rowid {
+            d.push_str(" NOT NULL");
+        }
+        if c.attrs.word.contains("unique") {
+            d.push_str(" UNIQUE");
+        }
+        if let Some(default) = c.attrs.name_value.get("sql_default") {
+            d.push_str(&format!(" DEFAULT {}", default));
+        }
+        if let Some(check) = c.attrs.name_value.get("check") {
+            d.push_str(&format!(" CHECK ({})", check));
+        }
+        definitions.push(d);
+    }
+    if keys.len() > 1 {
+        definitions.push(format!("PRIMARY KEY ({})", keys.join(", ")));
+    }
+    // определения столбцов (DEFAULT, CHECK) могут содержать `{` и `}`,
+    // поэтому запрос собирается из частей, а не через format!
+    let definitions = format!(" ({})", definitions.join(", "));
+    quote! {
+        impl #impl_generics #krate::SqlSchema for #name #ty_generics #where_clause {
+            fn create_table_statement(table: &str) -> String {
+                ["CREATE TABLE ", table, #definitions].concat()
+            }
+
+            fn columns() -> ::std::vec::Vec<#krate::ColumnDef> {
+                vec![#(#columns),*]
+            }
+        }
+    }
+}
+
+// Тип столбца (type affinity) и допустимость NULL по типу поля
+fn affinity(ty: &Type) -> (Option<&'static str>, bool) {
+    match *ty {
+        Type::Reference(ref r) => affinity(&r.elem),
+        Type::Path(ref p) if p.qself.is_none() => {
+            let segment = match p.path.segments.last() {
+                Some(segment) => segment.into_value(),
+                None => return (None, false),
+            };
+            let inner = match segment.arguments {
+                PathArguments::AngleBracketed(ref args) => match args.args.first() {
+                    Some(arg) => match *arg.into_value() {
+                        GenericArgument::Type(ref ty) => Some(ty),
+                        _ => None,
+                    },
+                    None => None,
+                },
+                _ => None,
+            };
+            match (segment.ident.to_string().as_str(), inner) {
+                ("Option", Some(inner)) => (affinity(inner).0, true),
+                ("Vec", Some(inner)) if is_u8(inner) => (Some("BLOB"), false),
+                ("i8", None)
+                | ("i16", None)
+                | ("i32", None)
+                | ("i64", None)
+                | ("isize", None)
+                | ("u8", None)
+                | ("u16", None)
+                | ("u32", None)
+                | ("u64", None)
+                | ("usize", None)
+                | ("bool", None) => (Some("INTEGER"), false),
+                ("f32", None) | ("f64", None) => (Some("REAL"), false),
+                ("String", None) | ("str", None) => (Some("TEXT"), false),
+                _ => (None, false),
+            }
+        }
+        Type::Slice(ref s) if is_u8(&s.elem) => (Some("BLOB"), false),
+        _ => (None, false),
+    }
+}
+
+fn is_u8(ty: &Type) -> bool {
+    match *ty {
+        Type::Path(ref p) => p.qself.is_none() && p.path.is_ident("u8"),
+        _ => false,
+    }
+}
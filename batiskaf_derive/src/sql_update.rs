@@ -2,10 +2,15 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::DeriveInput;
 
-use crate::column::{columns, Column};
+use crate::check::check_table;
+use crate::column::{columns, crate_path, parse_attributes, Column};
 
 pub(crate) fn derive(input: DeriveInput) -> TokenStream {
-    let name = input.ident;
+    let name = &input.ident;
+    let krate = match crate_path(&parse_attributes(&input.attrs)) {
+        Ok(krate) => krate,
+        Err(e) => return e.to_compile_error(),
+    };
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     let cs = columns(&input.data);
     let cs: Vec<Column> = cs.into_iter().filter(|c| !c.skip()).collect();
@@ -40,8 +45,11 @@ pub(crate) fn derive(input: DeriveInput) -> TokenStream {
         values.join(", "),
         keys.join(" AND ")
     );
+    if let Err(e) = check_table(&input, &sql) {
+        return e.to_compile_error();
+    }
     quote! {
-        impl #impl_generics ::batiskaf::SqlUpdate for #name #ty_generics #where_clause {
+        impl #impl_generics #krate::SqlUpdate for #name #ty_generics #where_clause {
             fn update_statement(table: &str) -> String {
                 format!(#sql, table)
             }
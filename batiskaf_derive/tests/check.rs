@@ -0,0 +1,39 @@
+use batiskaf::{SqlDelete, SqlInsert, SqlUpdate};
+use batiskaf_derive::*;
+
+#[test]
+fn test_check_sql() {
+    let sql = check_sql!(
+        "tests/schema.sql",
+        "select id, full_name, age from person where id = :id"
+    );
+    assert_eq!("select id, full_name, age from person where id = :id", sql);
+}
+
+#[test]
+fn test_checked_derive() {
+    #[allow(unused)]
+    #[derive(SqlInsert, SqlUpdate, SqlDelete)]
+    #[batiskaf(schema = "tests/schema.sql", table = "person")]
+    struct Person {
+        #[batiskaf(primary_key, autogenerated)]
+        id: i64,
+        #[batiskaf(column = "full_name")]
+        name: String,
+        age: Option<u32>,
+        #[batiskaf(skip)]
+        hobby: Option<String>,
+    }
+    assert_eq!(
+        "INSERT INTO person (full_name, age) VALUES (:full_name, :age)",
+        Person::insert_statement("person")
+    );
+    assert_eq!(
+        "UPDATE person SET full_name = :full_name, age = :age WHERE id = :id",
+        Person::update_statement("person")
+    );
+    assert_eq!(
+        "DELETE FROM person WHERE id = :id",
+        Person::delete_statement("person")
+    );
+}
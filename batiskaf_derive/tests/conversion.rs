@@ -0,0 +1,91 @@
+use std::net::Ipv4Addr;
+
+use rusqlite::types::{FromSqlError, FromSqlResult, ToSqlOutput, Value, ValueRef};
+use rusqlite::Connection;
+
+use batiskaf::BatiskafConnection;
+use batiskaf_derive::*;
+
+mod comma_separated {
+    use super::*;
+
+    pub fn from_sql(value: ValueRef<'_>) -> FromSqlResult<Vec<String>> {
+        let s = value.as_str()?;
+        if s.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(s.split(',').map(|x| x.to_string()).collect())
+    }
+
+    pub fn to_sql(value: &[String]) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Owned(Value::Text(value.join(","))))
+    }
+}
+
+fn ip_from_sql(value: ValueRef<'_>) -> FromSqlResult<Ipv4Addr> {
+    match value {
+        ValueRef::Integer(i) if 0 <= i && i <= i64::from(u32::MAX) => Ok(Ipv4Addr::from(i as u32)),
+        ValueRef::Integer(i) => Err(FromSqlError::OutOfRange(i)),
+        _ => Err(FromSqlError::InvalidType),
+    }
+}
+
+fn ip_to_sql(value: &Ipv4Addr) -> rusqlite::Result<ToSqlOutput<'_>> {
+    Ok(ToSqlOutput::Owned(Value::Integer(i64::from(u32::from(
+        *value,
+    )))))
+}
+
+#[derive(Debug, Eq, PartialEq, SqlParam, SqlResult, SqlInsert)]
+struct Host {
+    #[batiskaf(primary_key, autogenerated)]
+    pub id: i64,
+    #[batiskaf(from_sql = "ip_from_sql", to_sql = "ip_to_sql")]
+    pub ip: Ipv4Addr,
+    #[batiskaf(with = "comma_separated", default)]
+    pub tags: Vec<String>,
+}
+
+fn create_table(conn: &Connection) {
+    conn.execute_batch("create table host (id integer primary key, ip, tags text)")
+        .unwrap();
+}
+
+#[test]
+fn test_conversion() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    let mut host = Host {
+        id: 0,
+        ip: Ipv4Addr::new(192, 168, 0, 1),
+        tags: vec!["web".to_string(), "db".to_string()],
+    };
+    host.id = conn.insert("host", &host).unwrap();
+    let (ip, tags): (i64, String) = conn
+        .query_row("select ip, tags from host", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .unwrap();
+    assert_eq!(3_232_235_521, ip);
+    assert_eq!("web,db", tags);
+    let stored: Host = conn
+        .select_one("select id, ip, tags from host", &[])
+        .unwrap();
+    assert_eq!(host, stored);
+    let stored: Host = conn.select_one("select id, ip from host", &[]).unwrap();
+    assert!(stored.tags.is_empty());
+}
+
+#[test]
+fn test_conversion_error() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    conn.execute_batch("insert into host (ip, tags) values ('localhost', '')")
+        .unwrap();
+    match conn.select_one::<Host>("select id, ip, tags from host", &[]) {
+        Err(rusqlite::Error::InvalidColumnType(1, name, rusqlite::types::Type::Text)) => {
+            assert_eq!("ip", name)
+        }
+        x => panic!("unexpected result: {:?}", x),
+    }
+}
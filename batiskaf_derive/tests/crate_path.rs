@@ -0,0 +1,39 @@
+use batiskaf_derive::*;
+
+mod db {
+    pub use batiskaf as inner;
+}
+
+use db::inner::rusqlite::Connection;
+use db::inner::{BatiskafConnection, SqlSchema};
+
+#[derive(Debug, Eq, PartialEq, SqlParam, SqlResult, SqlInsert, SqlUpdate, SqlDelete, SqlSchema)]
+#[batiskaf(crate = "db::inner")]
+struct Person {
+    #[batiskaf(primary_key, autogenerated)]
+    pub id: i64,
+    pub name: String,
+    pub age: Option<u32>,
+}
+
+#[mapper(crate = "db::inner")]
+trait PersonMapper {
+    #[select("select id, name, age from person where name = :name")]
+    fn find_by_name(&self, name: &str) -> db::inner::rusqlite::Result<Vec<Person>>;
+}
+
+#[test]
+fn test_crate_path() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(&Person::create_table_statement("person"))
+        .unwrap();
+    let mut bob = Person {
+        id: 0,
+        name: "Bob".to_string(),
+        age: Some(30),
+    };
+    bob.id = conn.insert("person", &bob).unwrap();
+    bob.age = Some(31);
+    assert_eq!(1, conn.update("person", &bob).unwrap());
+    assert_eq!(vec![bob], conn.find_by_name("Bob").unwrap());
+}
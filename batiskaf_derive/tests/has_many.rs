@@ -0,0 +1,267 @@
+use rusqlite::Connection;
+
+use batiskaf::{BatiskafConnection, HasMany, SqlInsert};
+use batiskaf_derive::*;
+
+#[derive(Debug, PartialEq, SqlResult, SqlInsert, SqlParam)]
+struct Order {
+    #[batiskaf(primary_key, autogenerated)]
+    pub id: i64,
+    pub customer: String,
+    #[batiskaf(has_many = "OrderLine", foreign_key = "order_id")]
+    pub lines: Vec<OrderLine>,
+}
+
+#[derive(Clone, Debug, PartialEq, SqlResult)]
+struct OrderLine {
+    pub product: String,
+    pub quantity: u32,
+}
+
+fn create_tables(conn: &Connection) {
+    conn.execute_batch(
+        "create table orders (id integer primary key, customer text not null);
+         create table order_line (
+             id integer primary key,
+             order_id integer not null references orders (id),
+             product text not null,
+             quantity integer not null
+         );",
+    )
+    .unwrap();
+}
+
+fn line(product: &str, quantity: u32) -> OrderLine {
+    OrderLine {
+        product: product.to_string(),
+        quantity,
+    }
+}
+
+#[test]
+fn test_has_many() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_tables(&conn);
+    assert_eq!("order_id", <Order as HasMany<OrderLine>>::foreign_key());
+    assert_eq!(
+        "INSERT INTO orders (customer) VALUES (:customer)",
+        Order::insert_statement("orders")
+    );
+    for customer in &["Alice", "Bob", "Eve"] {
+        let order = Order {
+            id: 0,
+            customer: customer.to_string(),
+            lines: Vec::new(),
+        };
+        conn.insert("orders", &order).unwrap();
+    }
+    conn.execute_batch(
+        "insert into order_line (order_id, product, quantity) values
+             (1, 'apple', 3), (3, 'pear', 1), (1, 'plum', 2);",
+    )
+    .unwrap();
+    let mut orders: Vec<Order> = conn
+        .select_many("select id, customer from orders order by id", &[])
+        .unwrap();
+    assert!(orders.iter().all(|order| order.lines.is_empty()));
+    conn.load_has_many::<OrderLine, _>(&mut orders, "order_line")
+        .unwrap();
+    // без order_by дочерние записи упорядочены по rowid
+    let lines: Vec<&[OrderLine]> = orders.iter().map(|order| &order.lines[..]).collect();
+    assert_eq!(
+        vec![
+            &[line("apple", 3), line("plum", 2)][..],
+            &[][..],
+            &[line("pear", 1)][..]
+        ],
+        lines
+    );
+    let mut empty: Vec<Order> = Vec::new();
+    conn.load_has_many::<OrderLine, _>(&mut empty, "order_line")
+        .unwrap();
+}
+
+#[test]
+fn test_has_many_chunks() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_tables(&conn);
+    conn.execute_batch(
+        "with recursive n(x) as (select 1 union all select x + 1 from n where x < 1200)
+         insert into orders (id, customer) select x, 'c' || x from n;
+         insert into order_line (order_id, product, quantity) select id, 'p', id from orders;",
+    )
+    .unwrap();
+    let mut orders: Vec<Order> = conn
+        .select_many("select id, customer from orders", &[])
+        .unwrap();
+    conn.load_has_many::<OrderLine, _>(&mut orders, "order_line")
+        .unwrap();
+    assert_eq!(1200, orders.len());
+    for order in &orders {
+        assert_eq!(vec![line("p", order.id as u32)], order.lines);
+    }
+}
+
+#[test]
+fn test_has_many_duplicate_keys() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_tables(&conn);
+    conn.execute_batch(
+        "insert into orders (id, customer) values (1, 'Alice'), (2, 'Bob');
+         insert into order_line (order_id, product, quantity) values (1, 'apple', 3), (2, 'pear', 1);",
+    )
+    .unwrap();
+    // одинаковые родители из соединения с другой таблицей
+    let mut orders: Vec<Order> = conn
+        .select_many(
+            "select o.id, o.customer from orders o, (select 1 union all select 2) order by o.id",
+            &[],
+        )
+        .unwrap();
+    conn.load_has_many::<OrderLine, _>(&mut orders, "order_line")
+        .unwrap();
+    let lines: Vec<&[OrderLine]> = orders.iter().map(|order| &order.lines[..]).collect();
+    assert_eq!(
+        vec![
+            &[line("apple", 3)][..],
+            &[line("apple", 3)][..],
+            &[line("pear", 1)][..],
+            &[line("pear", 1)][..]
+        ],
+        lines
+    );
+}
+
+#[test]
+fn test_has_many_order_by() {
+    #[derive(Debug, PartialEq, SqlResult)]
+    struct SortedOrder {
+        #[batiskaf(primary_key)]
+        pub id: i64,
+        #[batiskaf(
+            has_many = "OrderLine",
+            foreign_key = "order_id",
+            order_by = "quantity desc, product"
+        )]
+        pub lines: Vec<OrderLine>,
+    }
+
+    let conn = Connection::open_in_memory().unwrap();
+    create_tables(&conn);
+    conn.execute_batch(
+        "insert into orders (id, customer) values (1, 'Alice');
+         insert into order_line (order_id, product, quantity) values
+             (1, 'apple', 1), (1, 'plum', 2), (1, 'pear', 1);",
+    )
+    .unwrap();
+    assert_eq!(
+        "quantity desc, product",
+        <SortedOrder as HasMany<OrderLine>>::order_by()
+    );
+    let mut orders: Vec<SortedOrder> = conn.select_many("select id from orders", &[]).unwrap();
+    conn.load_has_many::<OrderLine, _>(&mut orders, "order_line")
+        .unwrap();
+    assert_eq!(
+        vec![line("plum", 2), line("apple", 1), line("pear", 1)],
+        orders[0].lines
+    );
+}
+
+#[test]
+fn test_has_many_deny_unknown_columns() {
+    // запрос дочерних записей выбирает все столбцы таблицы, включая внешний ключ
+    #[derive(Clone, Debug, PartialEq, SqlResult)]
+    #[batiskaf(deny_unknown_columns)]
+    struct StrictLine {
+        pub product: String,
+        pub quantity: u32,
+    }
+
+    #[derive(Clone, Debug, PartialEq, SqlResult)]
+    #[batiskaf(deny_unknown_columns)]
+    struct FullLine {
+        pub id: i64,
+        pub order_id: i64,
+        pub product: String,
+        pub quantity: u32,
+    }
+
+    #[derive(Debug, PartialEq, SqlResult)]
+    struct StrictOrder {
+        #[batiskaf(primary_key)]
+        pub id: i64,
+        #[batiskaf(has_many = "StrictLine", foreign_key = "order_id")]
+        pub strict: Vec<StrictLine>,
+        #[batiskaf(has_many = "FullLine", foreign_key = "order_id")]
+        pub full: Vec<FullLine>,
+    }
+
+    let conn = Connection::open_in_memory().unwrap();
+    create_tables(&conn);
+    conn.execute_batch(
+        "insert into orders (id, customer) values (1, 'Alice');
+         insert into order_line (order_id, product, quantity) values (1, 'apple', 3);",
+    )
+    .unwrap();
+    let mut orders: Vec<StrictOrder> = conn.select_many("select id from orders", &[]).unwrap();
+    let err = conn
+        .load_has_many::<StrictLine, _>(&mut orders, "order_line")
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("unknown columns in query result: id, order_id"));
+    conn.load_has_many::<FullLine, _>(&mut orders, "order_line")
+        .unwrap();
+    assert_eq!(
+        vec![FullLine {
+            id: 1,
+            order_id: 1,
+            product: "apple".to_string(),
+            quantity: 3
+        }],
+        orders[0].full
+    );
+}
+
+#[test]
+fn test_has_many_async() {
+    use batiskaf::AsyncConnection;
+
+    let conn = block_on(AsyncConnection::open_in_memory()).unwrap();
+    block_on(conn.call(|conn| {
+        create_tables(conn);
+        conn.execute_batch(
+            "insert into orders (id, customer) values (1, 'Alice');
+             insert into order_line (order_id, product, quantity) values (1, 'apple', 3);",
+        )
+    }))
+    .unwrap();
+    let orders: Vec<Order> =
+        block_on(conn.select_many("select id, customer from orders", &[])).unwrap();
+    let orders = block_on(conn.load_has_many::<OrderLine, _>(orders, "order_line")).unwrap();
+    assert_eq!(vec![line("apple", 3)], orders[0].lines);
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+    use std::thread::{self, Thread};
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+use batiskaf::json::JsonError;
+use batiskaf::{BatiskafConnection, SqlSchema};
+use batiskaf_derive::*;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Settings {
+    theme: String,
+    font_size: u32,
+}
+
+#[derive(Debug, PartialEq, SqlParam, SqlResult, SqlInsert, SqlSchema)]
+struct Profile {
+    #[batiskaf(primary_key, autogenerated)]
+    pub id: i64,
+    #[batiskaf(json)]
+    pub settings: Settings,
+    #[batiskaf(json)]
+    pub labels: BTreeMap<String, i32>,
+    #[batiskaf(json)]
+    pub extra: Option<Vec<String>>,
+}
+
+fn create_table(conn: &Connection) {
+    conn.execute_batch(&Profile::create_table_statement("profile"))
+        .unwrap();
+}
+
+#[test]
+fn test_json() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    let mut labels = BTreeMap::new();
+    labels.insert("a".to_string(), 1);
+    let mut profile = Profile {
+        id: 0,
+        settings: Settings {
+            theme: "dark".to_string(),
+            font_size: 12,
+        },
+        labels,
+        extra: None,
+    };
+    profile.id = conn.insert("profile", &profile).unwrap();
+    let (settings, extra): (String, Option<String>) = conn
+        .query_row("select settings, extra from profile", [], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .unwrap();
+    assert_eq!(r#"{"theme":"dark","font_size":12}"#, settings);
+    assert_eq!(None, extra);
+    let stored: Profile = conn
+        .select_one("select id, settings, labels, extra from profile", &[])
+        .unwrap();
+    assert_eq!(profile, stored);
+}
+
+#[test]
+fn test_json_error() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    conn.execute_batch("insert into profile (settings, labels) values ('{\"theme\": 1}', '{}')")
+        .unwrap();
+    match conn.select_one::<Profile>("select id, settings, labels, extra from profile", &[]) {
+        Err(rusqlite::Error::FromSqlConversionFailure(1, rusqlite::types::Type::Text, err)) => {
+            let err = err.downcast_ref::<JsonError>().unwrap();
+            assert_eq!("settings", err.column);
+        }
+        x => panic!("unexpected result: {:?}", x),
+    }
+}
@@ -0,0 +1,197 @@
+use rusqlite::Connection;
+
+use batiskaf_derive::*;
+
+#[derive(Debug, Eq, PartialEq, SqlParam, SqlResult)]
+struct Person {
+    pub id: i64,
+    pub name: String,
+    pub age: Option<u32>,
+}
+
+#[derive(SqlParam)]
+struct PersonFilter<'a> {
+    #[batiskaf(expand)]
+    pub ids: &'a [i64],
+    pub age: u32,
+}
+
+#[mapper]
+trait PersonMapper {
+    #[select("select id, name, age from person where id = :id")]
+    fn find_person(&self, id: i64) -> rusqlite::Result<Option<Person>>;
+
+    #[select("select id, name, age from person where name = :name")]
+    fn get_person(&self, name: &str) -> rusqlite::Result<Person>;
+
+    #[select("select id, name, age from person where age >= :age order by id")]
+    fn find_older(&self, age: u32) -> rusqlite::Result<Vec<Person>>;
+
+    #[select("select id, name, age from person where id in (:ids) and age >= :age order by id")]
+    fn find_by_ids(&self, filter: &PersonFilter) -> rusqlite::Result<Vec<Person>>;
+
+    // параметры в комментариях не учитываются: filter - структура SqlParam
+    #[select(
+        "select id, name, age from person -- :filter
+         where id in (:ids) /* :filter */ and age >= :age order by id"
+    )]
+    fn find_by_filter(&self, filter: &PersonFilter) -> rusqlite::Result<Vec<Person>>;
+
+    // имена аргументов не пересекаются с локальными переменными сгенерированного метода
+    #[select("select id, name, age from person where id in (:ids) and age >= :age order by id")]
+    fn find_by_params(&self, params: &PersonFilter) -> rusqlite::Result<Vec<Person>>;
+
+    #[select("select id, name, age from person where name = :sql")]
+    fn find_by_sql(&self, sql: &str) -> rusqlite::Result<Option<Person>>;
+
+    #[insert("insert into person (name, age) values (:name, :age)")]
+    fn insert_person(&self, person: &Person) -> rusqlite::Result<usize>;
+
+    #[update("update person set age = :age where id = :id")]
+    fn update_age(&self, id: i64, age: Option<u32>) -> rusqlite::Result<usize>;
+
+    #[delete("delete from person where id = :id")]
+    fn delete_person(&self, id: i64) -> rusqlite::Result<usize>;
+
+    fn count_older(&self, age: u32) -> rusqlite::Result<usize> {
+        self.find_older(age).map(|xs| xs.len())
+    }
+}
+
+fn create_table(conn: &Connection) {
+    conn.execute(
+        "create table person (id integer primary key, name text not null, age integer)",
+        [],
+    )
+    .unwrap();
+}
+
+fn person(id: i64, name: &str, age: Option<u32>) -> Person {
+    Person {
+        id,
+        name: name.to_string(),
+        age,
+    }
+}
+
+#[test]
+fn test_select() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    assert_eq!(
+        1,
+        conn.insert_person(&person(0, "Alice", Some(33))).unwrap()
+    );
+    assert_eq!(1, conn.insert_person(&person(0, "Bob", Some(30))).unwrap());
+    assert_eq!(
+        Some(person(2, "Bob", Some(30))),
+        conn.find_person(2).unwrap()
+    );
+    assert_eq!(None, conn.find_person(3).unwrap());
+    assert_eq!(
+        person(1, "Alice", Some(33)),
+        conn.get_person("Alice").unwrap()
+    );
+    match conn.get_person("Carol") {
+        Err(rusqlite::Error::QueryReturnedNoRows) => (),
+        x => panic!("unexpected result: {:?}", x),
+    }
+    assert_eq!(
+        vec![person(1, "Alice", Some(33)), person(2, "Bob", Some(30))],
+        conn.find_older(30).unwrap()
+    );
+    assert_eq!(1, conn.count_older(31).unwrap());
+}
+
+#[test]
+fn test_update_delete() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    conn.insert_person(&person(0, "Bob", Some(30))).unwrap();
+    assert_eq!(1, conn.update_age(1, None).unwrap());
+    assert_eq!(0, conn.update_age(2, None).unwrap());
+    assert_eq!(Some(person(1, "Bob", None)), conn.find_person(1).unwrap());
+    assert_eq!(1, conn.delete_person(1).unwrap());
+    assert_eq!(None, conn.find_person(1).unwrap());
+}
+
+#[test]
+fn test_expand() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    conn.insert_person(&person(0, "Alice", Some(33))).unwrap();
+    conn.insert_person(&person(0, "Bob", Some(30))).unwrap();
+    conn.insert_person(&person(0, "Eve", Some(20))).unwrap();
+    let filter = PersonFilter {
+        ids: &[1, 3, 2],
+        age: 25,
+    };
+    assert_eq!(
+        vec![person(1, "Alice", Some(33)), person(2, "Bob", Some(30))],
+        conn.find_by_ids(&filter).unwrap()
+    );
+    assert_eq!(
+        vec![person(1, "Alice", Some(33)), person(2, "Bob", Some(30))],
+        conn.find_by_filter(&filter).unwrap()
+    );
+    let filter = PersonFilter { ids: &[], age: 0 };
+    assert_eq!(Vec::<Person>::new(), conn.find_by_ids(&filter).unwrap());
+}
+
+#[test]
+fn test_argument_names() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    conn.insert_person(&person(0, "Alice", Some(33))).unwrap();
+    conn.insert_person(&person(0, "Bob", Some(30))).unwrap();
+    let params = PersonFilter {
+        ids: &[2, 1],
+        age: 31,
+    };
+    assert_eq!(
+        vec![person(1, "Alice", Some(33))],
+        conn.find_by_params(&params).unwrap()
+    );
+    assert_eq!(
+        Some(person(2, "Bob", Some(30))),
+        conn.find_by_sql("Bob").unwrap()
+    );
+}
+
+#[test]
+fn test_deny_unknown_columns() {
+    use batiskaf::convert::UnknownColumns;
+
+    #[derive(Debug, SqlResult)]
+    #[batiskaf(deny_unknown_columns)]
+    struct Name {
+        #[allow(unused)]
+        pub name: String,
+    }
+
+    #[mapper]
+    trait NameMapper {
+        #[select("select * from person where id = :id")]
+        fn find_name(&self, id: i64) -> rusqlite::Result<Option<Name>>;
+
+        #[select("select * from person where id = :id")]
+        fn get_name(&self, id: i64) -> rusqlite::Result<Name>;
+    }
+
+    fn unknown<T: std::fmt::Debug>(result: rusqlite::Result<T>) -> Vec<String> {
+        match result {
+            Err(rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Null, err)) => {
+                err.downcast_ref::<UnknownColumns>()
+                    .unwrap()
+                    .columns
+                    .clone()
+            }
+            x => panic!("unexpected result: {:?}", x),
+        }
+    }
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    conn.insert_person(&person(0, "Alice", Some(33))).unwrap();
+    assert_eq!(vec!["id", "age"], unknown(conn.find_name(1)));
+    assert_eq!(vec!["id", "age"], unknown(conn.get_name(1)));
+}
@@ -0,0 +1,22 @@
+use rusqlite::Connection;
+
+use batiskaf::migrate::Migrator;
+use batiskaf_derive::*;
+
+#[test]
+fn test_include_migrations() {
+    let migrations: &[(&str, &str)] = include_migrations!("tests/migrations");
+    assert_eq!(
+        vec!["0001_create_person", "0002_add_age"],
+        migrations.iter().map(|m| m.0).collect::<Vec<_>>()
+    );
+    let mut conn = Connection::open_in_memory().unwrap();
+    let migrator = Migrator::new(migrations);
+    assert_eq!(
+        vec!["0001_create_person", "0002_add_age"],
+        migrator.run(&mut conn).unwrap()
+    );
+    assert!(migrator.run(&mut conn).unwrap().is_empty());
+    conn.execute("insert into person (name, age) values ('Bob', 30)", [])
+        .unwrap();
+}
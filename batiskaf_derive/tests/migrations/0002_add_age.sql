@@ -0,0 +1 @@
+alter table person add column age integer;
@@ -0,0 +1,73 @@
+use rusqlite::Connection;
+
+use batiskaf_derive::*;
+
+#[derive(Debug, Eq, PartialEq, SqlParam, SqlResult)]
+struct Person {
+    pub id: i64,
+    pub name: String,
+    pub age: Option<u32>,
+}
+
+#[derive(SqlResult)]
+struct Count {
+    pub n: i64,
+}
+
+mod person {
+    use super::{Count, Person};
+
+    batiskaf_derive::include_queries!("tests/queries/person.sql");
+}
+
+fn bob() -> Person {
+    Person {
+        id: 0,
+        name: "Bob".to_string(),
+        age: Some(30),
+    }
+}
+
+#[test]
+fn test_include_queries() {
+    let conn = Connection::open_in_memory().unwrap();
+    assert_eq!(0, person::create_person_table(&conn).unwrap());
+    assert_eq!(1, person::insert_person(&conn, &bob()).unwrap());
+    assert_eq!(
+        Some(Person { id: 1, ..bob() }),
+        person::find_person_by_id(&conn, 1).unwrap()
+    );
+    assert_eq!(None, person::find_person_by_id(&conn, 2).unwrap());
+    assert_eq!(1, person::find_people_older_than(&conn, 29).unwrap().len());
+    assert!(person::find_people_older_than(&conn, 30)
+        .unwrap()
+        .is_empty());
+    assert_eq!(1, person::count_people(&conn).unwrap().n);
+}
+
+#[test]
+fn test_update_query() {
+    let conn = Connection::open_in_memory().unwrap();
+    person::create_person_table(&conn).unwrap();
+    person::insert_person(&conn, &bob()).unwrap();
+    assert_eq!(1, person::rename_person(&conn, 1, "Robert").unwrap());
+    assert_eq!(
+        "Robert",
+        person::find_person_by_id(&conn, 1).unwrap().unwrap().name
+    );
+}
+
+#[test]
+fn test_parameter_names() {
+    let conn = Connection::open_in_memory().unwrap();
+    person::create_person_table(&conn).unwrap();
+    person::insert_person(&conn, &bob()).unwrap();
+    assert_eq!(
+        Some(Person { id: 1, ..bob() }),
+        person::find_person_by_name_and_age(&conn, "Bob", 30).unwrap()
+    );
+    assert_eq!(
+        None,
+        person::find_person_by_name_and_age(&conn, "Bob", 31).unwrap()
+    );
+}
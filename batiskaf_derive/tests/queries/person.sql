@@ -0,0 +1,40 @@
+-- Запросы для таблицы person
+
+-- name: create_person_table
+create table person (
+    id integer primary key,
+    name text not null,
+    age integer
+)
+
+-- name: insert_person
+-- params: person: &Person
+insert into person (name, age) values (:name, :age)
+
+-- name: find_person_by_id
+-- params: id: i64
+-- returns: Option<Person>
+select id, name, age from person where id = :id
+
+-- name: find_people_older_than
+-- params: age: u32
+-- returns: Vec<Person>
+-- people with unknown age are ignored
+select id, name, age
+from person
+where age > :age
+order by id
+
+-- name: count_people
+-- returns: Count
+select count(*) as n from person
+
+-- name: rename_person
+-- params: id: i64, name: &str
+update person set name = :name where id = :id
+
+-- name: find_person_by_name_and_age
+-- params: conn: &str, sql: u32
+-- returns: Option<Person>
+-- имена параметров не пересекаются с переменными сгенерированной функции
+select id, name, age from person where name = :conn and age = :sql
@@ -0,0 +1,6 @@
+-- Имя параметра занято сгенерированным кодом
+
+-- name: find_person_by_id
+-- params: __batiskaf_id: i64
+-- returns: Option<i64>
+select id from person where id = :__batiskaf_id
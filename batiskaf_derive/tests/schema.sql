@@ -0,0 +1,5 @@
+create table person (
+    id integer primary key,
+    full_name text not null,
+    age integer
+);
@@ -1,4 +1,7 @@
-use rusqlite::{Connection, NO_PARAMS};
+// тесты сохраняют исходный вид: поле age не читается, ToSqlOutput без явного времени жизни
+#![allow(dead_code, mismatched_lifetime_syntaxes)]
+
+use rusqlite::Connection;
 
 use batiskaf::SqlParam;
 use batiskaf_derive::*;
@@ -14,7 +17,7 @@ fn test_sql_param() {
     let conn = Connection::open_in_memory().unwrap();
     conn.execute(
         "create table person (id integer primary key, name text not null, age integer)",
-        NO_PARAMS,
+        [],
     )
     .unwrap();
     let mut stmt = conn
@@ -25,11 +28,11 @@ fn test_sql_param() {
         name: "Bob".to_string(),
         age: Some(30),
     };
-    let params = person.to_named_params(&stmt);
-    stmt.execute_named(&params).unwrap();
+    let params = person.to_params(&stmt);
+    stmt.execute(params).unwrap();
     let mut select = conn.prepare("select id, name, age from person").unwrap();
     let x = select
-        .query_row(NO_PARAMS, |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .query_row([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
         .unwrap();
     assert_eq!((1, "Bob".to_string(), 30), x);
 }
@@ -61,7 +64,7 @@ fn test_custom_to_sql() {
     let conn = Connection::open_in_memory().unwrap();
     conn.execute(
         "create table \"order\" (id integer primary key, status integer)",
-        NO_PARAMS,
+        [],
     )
     .unwrap();
     let mut stmt = conn
@@ -71,11 +74,11 @@ fn test_custom_to_sql() {
         id: 0,
         status: Status::New,
     };
-    let params = order.to_named_params(&stmt);
-    stmt.execute_named(&params).unwrap();
+    let params = order.to_params(&stmt);
+    stmt.execute(params).unwrap();
     let mut select = conn.prepare("select id, status from \"order\"").unwrap();
     let x = select
-        .query_row(NO_PARAMS, |row| Ok((row.get(0)?, row.get(1)?)))
+        .query_row([], |row| Ok((row.get(0)?, row.get(1)?)))
         .unwrap();
     assert_eq!((1, 1), x);
 }
@@ -92,7 +95,7 @@ fn test_skip_param() {
     let conn = Connection::open_in_memory().unwrap();
     conn.execute(
         "create table person (id integer primary key, name text not null, age integer)",
-        NO_PARAMS,
+        [],
     )
     .unwrap();
     let mut stmt = conn
@@ -103,11 +106,11 @@ fn test_skip_param() {
         name: "Bob".to_string(),
         age: Some(30),
     };
-    let params = person.to_named_params(&stmt);
-    stmt.execute_named(&params).unwrap();
+    let params = person.to_params(&stmt);
+    stmt.execute(params).unwrap();
     let mut select = conn.prepare("select id, name, age from person").unwrap();
     let x: (i64, String, Option<u32>) = select
-        .query_row(NO_PARAMS, |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .query_row([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
         .unwrap();
     assert_eq!((1, "Bob".to_string(), None), x);
 }
@@ -122,7 +125,7 @@ fn test_generic() {
     let conn = Connection::open_in_memory().unwrap();
     conn.execute(
         "create table key_value (key text not null, value text not null)",
-        NO_PARAMS,
+        [],
     )
     .unwrap();
     let mut stmt = conn
@@ -132,11 +135,75 @@ fn test_generic() {
         key: "name".to_string(),
         value: "Bob".to_string(),
     };
-    let params = kv.to_named_params(&stmt);
-    stmt.execute_named(&params).unwrap();
+    let params = kv.to_params(&stmt);
+    stmt.execute(params).unwrap();
     let mut select = conn.prepare("select key, value from key_value").unwrap();
     let x: (String, String) = select
-        .query_row(NO_PARAMS, |row| Ok((row.get(0)?, row.get(1)?)))
+        .query_row([], |row| Ok((row.get(0)?, row.get(1)?)))
         .unwrap();
     assert_eq!(("name".to_string(), "Bob".to_string()), x);
 }
+
+#[test]
+fn test_expand() {
+    use std::borrow::Cow;
+
+    use batiskaf::{BatiskafConnection, DynRow};
+
+    #[derive(SqlParam)]
+    struct Filter {
+        #[batiskaf(expand)]
+        pub ids: Vec<i64>,
+        #[batiskaf(expand, column = "names")]
+        pub name_list: Vec<String>,
+        pub age: Option<u32>,
+    }
+    let filter = Filter {
+        ids: vec![1, 3],
+        name_list: vec!["Alice".to_string()],
+        age: Some(30),
+    };
+    assert_eq!(
+        "select * from person where id in (:ids_0, :ids_1) and name in (:names_0) and ':ids' <> name",
+        filter.expand_sql(Cow::Borrowed(
+            "select * from person where id in (:ids) and name in (:names) and ':ids' <> name"
+        ))
+    );
+    let names: Vec<String> = filter
+        .expanded_params()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(vec![":ids_0", ":ids_1", ":names_0"], names);
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "create table person (id integer primary key, name text not null, age integer);
+         insert into person (name, age) values ('Alice', 33), ('Bob', 30), ('Eve', 20);",
+    )
+    .unwrap();
+    let sql = "select name from person where (id in (:ids) or name in (:names)) and age >= :age order by id";
+    let rows: Vec<DynRow> = conn.select_many_with(sql, &filter).unwrap();
+    let names: Vec<String> = rows.iter().map(|row| row.get("name").unwrap()).collect();
+    assert_eq!(vec!["Alice"], names);
+    let filter = Filter {
+        ids: vec![2, 3],
+        name_list: vec![],
+        age: None,
+    };
+    let stmt = conn
+        .prepare(&filter.expand_sql(Cow::Borrowed(sql)))
+        .unwrap();
+    assert_eq!(3, stmt.parameter_count());
+    assert_eq!(
+        2,
+        conn.execute_with("update person set age = 0 where id in (:ids)", &filter)
+            .unwrap()
+    );
+    let row: DynRow = conn
+        .select_one_with(
+            "select count(*) as n from person where id in (:ids) and age = 0",
+            &filter,
+        )
+        .unwrap();
+    assert_eq!(2, row.get::<i64>("n").unwrap());
+}
@@ -1,5 +1,8 @@
+// тесты сохраняют исходный вид: from_row(&row) для row: &Row
+#![allow(clippy::needless_borrow)]
+
 use rusqlite::types::ToSql;
-use rusqlite::{Connection, NO_PARAMS};
+use rusqlite::Connection;
 
 use batiskaf::SqlResult;
 use batiskaf_derive::*;
@@ -15,16 +18,16 @@ fn test_sql_result() {
     let conn = Connection::open_in_memory().unwrap();
     conn.execute(
         "create table person (id integer primary key, name text not null, age integer)",
-        NO_PARAMS,
+        [],
     )
     .unwrap();
     let mut stmt = conn
         .prepare("insert into person (name, age) values (:name, :age)")
         .unwrap();
-    stmt.execute_named(&[(":name", &"Bob" as &dyn ToSql), (":age", &30)])
+    stmt.execute(&[(":name", &"Bob" as &dyn ToSql), (":age", &30)])
         .unwrap();
     let mut select = conn.prepare("select id, name, age from person").unwrap();
-    let mut rows = select.query(NO_PARAMS).unwrap();
+    let mut rows = select.query([]).unwrap();
     let row = rows.next().unwrap().unwrap();
     let bob = Person::from_row(&row).unwrap();
     assert_eq!(
@@ -49,18 +52,18 @@ fn test_rename() {
     let conn = Connection::open_in_memory().unwrap();
     conn.execute(
         "create table person (id integer primary key, full_name text not null, age integer)",
-        NO_PARAMS,
+        [],
     )
     .unwrap();
     let mut stmt = conn
         .prepare("insert into person (full_name, age) values (:name, :age)")
         .unwrap();
-    stmt.execute_named(&[(":name", &"Bob" as &dyn ToSql), (":age", &30)])
+    stmt.execute(&[(":name", &"Bob" as &dyn ToSql), (":age", &30)])
         .unwrap();
     let mut select = conn
         .prepare("select id, full_name, age from person")
         .unwrap();
-    let mut rows = select.query(NO_PARAMS).unwrap();
+    let mut rows = select.query([]).unwrap();
     let row = rows.next().unwrap().unwrap();
     let bob = Person::from_row(&row).unwrap();
     assert_eq!(
@@ -85,16 +88,16 @@ fn test_skip() {
     let conn = Connection::open_in_memory().unwrap();
     conn.execute(
         "create table person (id integer primary key, name text not null, age integer)",
-        NO_PARAMS,
+        [],
     )
     .unwrap();
     let mut stmt = conn
         .prepare("insert into person (name, age) values (:name, :age)")
         .unwrap();
-    stmt.execute_named(&[(":name", &"Bob" as &dyn ToSql), (":age", &30)])
+    stmt.execute(&[(":name", &"Bob" as &dyn ToSql), (":age", &30)])
         .unwrap();
     let mut select = conn.prepare("select id, name, age from person").unwrap();
-    let mut rows = select.query(NO_PARAMS).unwrap();
+    let mut rows = select.query([]).unwrap();
     let row = rows.next().unwrap().unwrap();
     let bob = Person::from_row(&row).unwrap();
     assert_eq!(
@@ -119,16 +122,16 @@ fn test_default() {
     let conn = Connection::open_in_memory().unwrap();
     conn.execute(
         "create table person (id integer primary key, name text not null, age integer)",
-        NO_PARAMS,
+        [],
     )
     .unwrap();
     let mut stmt = conn
         .prepare("insert into person (name, age) values (:name, :age)")
         .unwrap();
-    stmt.execute_named(&[(":name", &"Bob" as &dyn ToSql), (":age", &30)])
+    stmt.execute(&[(":name", &"Bob" as &dyn ToSql), (":age", &30)])
         .unwrap();
     let mut select = conn.prepare("select id, name from person").unwrap();
-    let mut rows = select.query(NO_PARAMS).unwrap();
+    let mut rows = select.query([]).unwrap();
     let row = rows.next().unwrap().unwrap();
     let bob = Person::from_row(&row).unwrap();
     assert_eq!(
@@ -153,16 +156,16 @@ fn test_default_struct() {
     let conn = Connection::open_in_memory().unwrap();
     conn.execute(
         "create table person (id integer primary key, name text not null, age integer)",
-        NO_PARAMS,
+        [],
     )
     .unwrap();
     let mut stmt = conn
         .prepare("insert into person (name, age) values (:name, :age)")
         .unwrap();
-    stmt.execute_named(&[(":name", &"Bob" as &dyn ToSql), (":age", &30)])
+    stmt.execute(&[(":name", &"Bob" as &dyn ToSql), (":age", &30)])
         .unwrap();
     let mut select = conn.prepare("select id, name from person").unwrap();
-    let mut rows = select.query(NO_PARAMS).unwrap();
+    let mut rows = select.query([]).unwrap();
     let row = rows.next().unwrap().unwrap();
     let bob = Person::from_row(&row).unwrap();
     assert_eq!(
@@ -185,16 +188,16 @@ fn test_generic() {
     let conn = Connection::open_in_memory().unwrap();
     conn.execute(
         "create table key_value (key text not null, value text not null)",
-        NO_PARAMS,
+        [],
     )
     .unwrap();
     let mut stmt = conn
         .prepare("insert into key_value (key, value) values (:key, :value)")
         .unwrap();
-    stmt.execute_named(&[(":key", &"name" as &dyn ToSql), (":value", &"Bob")])
+    stmt.execute(&[(":key", &"name" as &dyn ToSql), (":value", &"Bob")])
         .unwrap();
     let mut select = conn.prepare("select key, value from key_value").unwrap();
-    let mut rows = select.query(NO_PARAMS).unwrap();
+    let mut rows = select.query([]).unwrap();
     let row = rows.next().unwrap().unwrap();
     let bob = KeyValue::<String>::from_row(&row).unwrap();
     assert_eq!("name".to_string(), bob.key);
@@ -212,16 +215,16 @@ fn test_generic_default() {
     let conn = Connection::open_in_memory().unwrap();
     conn.execute(
         "create table key_value (key text not null, value text not null)",
-        NO_PARAMS,
+        [],
     )
     .unwrap();
     let mut stmt = conn
         .prepare("insert into key_value (key, value) values (:key, :value)")
         .unwrap();
-    stmt.execute_named(&[(":key", &"name" as &dyn ToSql), (":value", &"Bob")])
+    stmt.execute(&[(":key", &"name" as &dyn ToSql), (":value", &"Bob")])
         .unwrap();
     let mut select = conn.prepare("select key, value from key_value").unwrap();
-    let mut rows = select.query(NO_PARAMS).unwrap();
+    let mut rows = select.query([]).unwrap();
     let row = rows.next().unwrap().unwrap();
     let bob = KeyValue::<String>::from_row(&row).unwrap();
     assert_eq!("name".to_string(), bob.key);
@@ -258,15 +261,15 @@ fn test_custom_from_sql() {
     let conn = Connection::open_in_memory().unwrap();
     conn.execute(
         "create table \"order\" (id integer primary key, status integer)",
-        NO_PARAMS,
+        [],
     )
     .unwrap();
     let mut stmt = conn
         .prepare("insert into \"order\" (status) values (:status)")
         .unwrap();
-    stmt.execute_named(&[(":status", &1)]).unwrap();
+    stmt.execute(&[(":status", &1)]).unwrap();
     let mut select = conn.prepare("select id, status from \"order\"").unwrap();
-    let mut rows = select.query(NO_PARAMS).unwrap();
+    let mut rows = select.query([]).unwrap();
     let row = rows.next().unwrap().unwrap();
     let order = Order::from_row(&row).unwrap();
     assert_eq!(
@@ -277,3 +280,405 @@ fn test_custom_from_sql() {
         order
     );
 }
+
+fn default_role() -> String {
+    "user".to_string()
+}
+
+#[test]
+fn test_default_path_and_value() {
+    #[derive(Debug, Eq, PartialEq, SqlResult)]
+    struct Account {
+        pub login: String,
+        #[batiskaf(default_value = "true")]
+        pub active: bool,
+        #[batiskaf(default = "default_role")]
+        pub role: String,
+        #[batiskaf(skip, default_value = "42")]
+        pub limit: u32,
+    }
+    let conn = Connection::open_in_memory().unwrap();
+    let mut select = conn.prepare("select 'bob' as login").unwrap();
+    let mut rows = select.query([]).unwrap();
+    let row = rows.next().unwrap().unwrap();
+    assert_eq!(
+        Account {
+            login: "bob".to_string(),
+            active: true,
+            role: "user".to_string(),
+            limit: 42,
+        },
+        Account::from_row(row).unwrap()
+    );
+    let mut select = conn
+        .prepare("select 'bob' as login, 0 as active, 'admin' as role")
+        .unwrap();
+    let mut rows = select.query([]).unwrap();
+    let row = rows.next().unwrap().unwrap();
+    let bob = Account::from_row(row).unwrap();
+    assert!(!bob.active);
+    assert_eq!("admin", bob.role);
+}
+
+#[test]
+fn test_default_with_schema() {
+    use batiskaf::{BatiskafConnection, SqlSchema};
+
+    // sql_default - DEFAULT столбца для SqlSchema, default_value - значение поля для SqlResult
+    #[derive(Debug, Eq, PartialEq, SqlResult, SqlSchema)]
+    struct Account {
+        #[batiskaf(primary_key)]
+        pub login: String,
+        #[batiskaf(sql_default = "1", default_value = "true")]
+        pub active: bool,
+    }
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(&Account::create_table_statement("account"))
+        .unwrap();
+    conn.execute_batch("insert into account (login) values ('bob')")
+        .unwrap();
+    let bob = Account {
+        login: "bob".to_string(),
+        active: true,
+    };
+    assert_eq!(
+        bob,
+        conn.select_one("select login, active from account", &[])
+            .unwrap()
+    );
+    assert_eq!(
+        bob,
+        conn.select_one("select login from account", &[]).unwrap()
+    );
+}
+
+#[test]
+fn test_generic_without_default() {
+    struct Id(i64);
+    impl ::rusqlite::types::FromSql for Id {
+        fn column_result(
+            value: ::rusqlite::types::ValueRef,
+        ) -> ::rusqlite::types::FromSqlResult<Self> {
+            value.as_i64().map(Id)
+        }
+    }
+    #[derive(SqlResult)]
+    struct Item<T> {
+        pub id: T,
+        #[batiskaf(default_value = "1")]
+        pub count: i64,
+    }
+    let conn = Connection::open_in_memory().unwrap();
+    let mut select = conn.prepare("select 7 as id").unwrap();
+    let mut rows = select.query([]).unwrap();
+    let row = rows.next().unwrap().unwrap();
+    let item = Item::<Id>::from_row(row).unwrap();
+    assert_eq!(7, item.id.0);
+    assert_eq!(1, item.count);
+}
+
+#[test]
+fn test_null_as_default() {
+    #[derive(Debug, Eq, PartialEq, SqlResult)]
+    struct Order {
+        pub id: i64,
+        #[batiskaf(null_as_default)]
+        pub comment: String,
+        #[batiskaf(null_as_default, default_value = "1")]
+        pub quantity: u32,
+        pub discount: Option<u32>,
+    }
+    let conn = Connection::open_in_memory().unwrap();
+    let mut select = conn
+        .prepare("select 1 as id, null as comment, null as quantity, null as discount")
+        .unwrap();
+    let mut rows = select.query([]).unwrap();
+    let row = rows.next().unwrap().unwrap();
+    assert_eq!(
+        Order {
+            id: 1,
+            comment: "".to_string(),
+            quantity: 1,
+            discount: None,
+        },
+        Order::from_row(row).unwrap()
+    );
+    let mut select = conn
+        .prepare("select 1 as id, null as quantity, null as discount")
+        .unwrap();
+    let mut rows = select.query([]).unwrap();
+    let row = rows.next().unwrap().unwrap();
+    match Order::from_row(row) {
+        Err(rusqlite::Error::InvalidColumnName(name)) => assert_eq!("comment", name),
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("missing column must be an error"),
+    }
+}
+
+#[test]
+fn test_null_as_default_struct() {
+    #[derive(Debug, Eq, PartialEq, SqlResult)]
+    #[batiskaf(null_as_default)]
+    struct Customer {
+        pub name: String,
+        pub orders: i64,
+    }
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "create table customer (id integer primary key, name text not null);
+         create table purchase (customer_id integer not null);
+         insert into customer (name) values ('Bob');",
+    )
+    .unwrap();
+    let mut select = conn
+        .prepare(
+            "select c.name, p.customer_id as orders
+             from customer c left join purchase p on p.customer_id = c.id",
+        )
+        .unwrap();
+    let mut rows = select.query([]).unwrap();
+    let row = rows.next().unwrap().unwrap();
+    assert_eq!(
+        Customer {
+            name: "Bob".to_string(),
+            orders: 0,
+        },
+        Customer::from_row(row).unwrap()
+    );
+}
+
+#[test]
+fn test_from_row_indexed() {
+    #[derive(Debug, Eq, PartialEq, SqlResult)]
+    struct Person {
+        pub id: i64,
+        #[batiskaf(column = "full_name")]
+        pub name: String,
+        #[batiskaf(default)]
+        pub age: Option<u32>,
+        #[batiskaf(skip, default_value = "true")]
+        pub active: bool,
+    }
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "create table person (id integer primary key, full_name text not null);
+         insert into person (full_name) values ('Bob');
+         insert into person (full_name) values ('Alice');",
+    )
+    .unwrap();
+    let mut select = conn
+        .prepare("select full_name, id from person order by id")
+        .unwrap();
+    assert_eq!(
+        vec![Some(1), Some(0), None, None],
+        Person::column_indices(&select)
+    );
+    let people: Vec<Person> = batiskaf::select_iter(&mut select, &[])
+        .unwrap()
+        .collect::<rusqlite::Result<_>>()
+        .unwrap();
+    assert_eq!(
+        vec![
+            Person {
+                id: 1,
+                name: "Bob".to_string(),
+                age: None,
+                active: true,
+            },
+            Person {
+                id: 2,
+                name: "Alice".to_string(),
+                age: None,
+                active: true,
+            },
+        ],
+        people
+    );
+    let mut select = conn.prepare("select id from person").unwrap();
+    let mut rows = batiskaf::select_iter::<Person>(&mut select, &[]).unwrap();
+    match rows.next() {
+        Some(Err(rusqlite::Error::InvalidColumnName(name))) => assert_eq!("full_name", name),
+        x => panic!("unexpected result: {:?}", x),
+    }
+}
+
+#[test]
+fn test_alias() {
+    #[derive(Debug, Eq, PartialEq, SqlResult)]
+    struct Person {
+        #[batiskaf(alias = "person_id, pid")]
+        pub id: i64,
+        pub name: String,
+    }
+    let conn = Connection::open_in_memory().unwrap();
+    for sql in &[
+        "select 1 as id, 'Bob' as name",
+        "select 1 as person_id, 'Bob' as name",
+        "select 1 as PID, 'Bob' as name",
+    ] {
+        let bob = Person {
+            id: 1,
+            name: "Bob".to_string(),
+        };
+        let mut select = conn.prepare(sql).unwrap();
+        let mut rows = select.query([]).unwrap();
+        let row = rows.next().unwrap().unwrap();
+        assert_eq!(bob, Person::from_row(row).unwrap());
+        let mut select = conn.prepare(sql).unwrap();
+        let mut rows = batiskaf::select_iter::<Person>(&mut select, &[]).unwrap();
+        assert_eq!(bob, rows.next().unwrap().unwrap());
+    }
+    let mut select = conn.prepare("select 'Bob' as name").unwrap();
+    let mut rows = select.query([]).unwrap();
+    let row = rows.next().unwrap().unwrap();
+    match Person::from_row(row) {
+        Err(rusqlite::Error::InvalidColumnName(name)) => assert_eq!("id", name),
+        x => panic!("unexpected result: {:?}", x),
+    }
+}
+
+#[test]
+fn test_case_insensitive() {
+    #[derive(Debug, Eq, PartialEq, SqlResult)]
+    #[batiskaf(case_insensitive)]
+    struct Person {
+        pub id: i64,
+        #[batiskaf(column = "имя")]
+        pub name: String,
+    }
+    let conn = Connection::open_in_memory().unwrap();
+    let sql = "select 1 as ID, 'Bob' as ИМЯ";
+    let bob = Person {
+        id: 1,
+        name: "Bob".to_string(),
+    };
+    let mut select = conn.prepare(sql).unwrap();
+    let mut rows = select.query([]).unwrap();
+    let row = rows.next().unwrap().unwrap();
+    assert_eq!(bob, Person::from_row(row).unwrap());
+    let mut select = conn.prepare(sql).unwrap();
+    let mut rows = batiskaf::select_iter::<Person>(&mut select, &[]).unwrap();
+    assert_eq!(bob, rows.next().unwrap().unwrap());
+}
+
+#[test]
+fn test_deny_unknown_columns() {
+    use batiskaf::convert::UnknownColumns;
+    use batiskaf::BatiskafConnection;
+
+    #[derive(Debug, Eq, PartialEq, SqlResult)]
+    #[batiskaf(deny_unknown_columns)]
+    struct Person {
+        #[batiskaf(alias = "person_id")]
+        pub id: i64,
+        pub name: String,
+        #[batiskaf(skip, default)]
+        pub age: Option<u32>,
+    }
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "create table person (id integer primary key, name text not null, age integer, email text);
+         insert into person (name, age, email) values ('Bob', 30, 'bob@example.com');",
+    )
+    .unwrap();
+    let bob = Person {
+        id: 1,
+        name: "Bob".to_string(),
+        age: None,
+    };
+    assert_eq!(
+        bob,
+        conn.select_one("select id as person_id, name from person", &[])
+            .unwrap()
+    );
+    fn unknown<T: std::fmt::Debug>(result: rusqlite::Result<T>) -> Vec<String> {
+        match result {
+            // age - первый неизвестный столбец
+            Err(rusqlite::Error::FromSqlConversionFailure(2, rusqlite::types::Type::Null, err)) => {
+                err.downcast_ref::<UnknownColumns>()
+                    .unwrap()
+                    .columns
+                    .clone()
+            }
+            x => panic!("unexpected result: {:?}", x),
+        }
+    }
+    assert_eq!(
+        vec!["age", "email"],
+        unknown(conn.select_one::<Person>("select * from person", &[]))
+    );
+    assert_eq!(
+        vec!["age", "email"],
+        unknown(conn.select_many::<Person>("select * from person", &[]))
+    );
+    assert_eq!(
+        vec!["age", "email"],
+        unknown(conn.select_one_with::<Person, _>("select * from person", &batiskaf::params! {}))
+    );
+    let mut select = conn.prepare("select * from person").unwrap();
+    assert_eq!(
+        vec!["age", "email"],
+        unknown(select.query_row([], Person::from_row))
+    );
+}
+
+#[test]
+fn test_rest() {
+    use std::collections::{BTreeMap, HashMap};
+
+    use batiskaf::{BatiskafConnection, SqlInsert};
+    use rusqlite::types::Value;
+
+    #[derive(Debug, PartialEq, SqlResult, SqlInsert)]
+    struct Sales {
+        #[batiskaf(alias = "area")]
+        pub region: String,
+        #[batiskaf(rest)]
+        pub months: HashMap<String, Value>,
+    }
+    #[derive(Debug, PartialEq, SqlResult)]
+    struct Totals {
+        #[batiskaf(rest)]
+        pub values: BTreeMap<String, Value>,
+        pub total: i64,
+    }
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "create table sales (region text not null, month text not null, amount integer);
+         insert into sales values ('north', 'jan', 10), ('north', 'feb', 20), ('south', 'jan', 5);",
+    )
+    .unwrap();
+    assert_eq!(
+        "INSERT INTO sales (region) VALUES (:region)",
+        Sales::insert_statement("sales")
+    );
+    let sql = "select region as area,
+                      sum(case month when 'jan' then amount end) as jan,
+                      sum(case month when 'feb' then amount end) as feb
+               from sales group by region order by region";
+    let months = |jan: Value, feb: Value| {
+        let mut m = HashMap::new();
+        m.insert("jan".to_string(), jan);
+        m.insert("feb".to_string(), feb);
+        m
+    };
+    let expected = vec![
+        Sales {
+            region: "north".to_string(),
+            months: months(Value::Integer(10), Value::Integer(20)),
+        },
+        Sales {
+            region: "south".to_string(),
+            months: months(Value::Integer(5), Value::Null),
+        },
+    ];
+    assert_eq!(expected, conn.select_many::<Sales>(sql, &[]).unwrap());
+    assert_eq!(expected[0], conn.select_one::<Sales>(sql, &[]).unwrap());
+    let totals: Totals = conn
+        .select_one("select 'x' as name, 1.5 as ratio, 3 as total", &[])
+        .unwrap();
+    let mut values = BTreeMap::new();
+    values.insert("name".to_string(), Value::Text("x".to_string()));
+    values.insert("ratio".to_string(), Value::Real(1.5));
+    assert_eq!(Totals { values, total: 3 }, totals);
+}
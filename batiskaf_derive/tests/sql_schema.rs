@@ -0,0 +1,176 @@
+use rusqlite::Connection;
+
+use batiskaf::{BatiskafConnection, ColumnDef, SqlSchema};
+use batiskaf_derive::*;
+
+#[test]
+fn test_simple() {
+    #[allow(unused)]
+    #[derive(SqlSchema)]
+    struct Person {
+        #[batiskaf(primary_key, autogenerated)]
+        id: i64,
+        #[batiskaf(column = "full_name")]
+        name: String,
+        age: Option<u32>,
+        photo: Option<Vec<u8>>,
+        #[batiskaf(skip)]
+        hobby: Option<String>,
+    }
+    let sql = Person::create_table_statement("person");
+    assert_eq!(
+        "CREATE TABLE person (id INTEGER PRIMARY KEY AUTOINCREMENT, full_name TEXT NOT NULL, age INTEGER, photo BLOB)",
+        sql
+    );
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute(&sql, []).unwrap();
+}
+
+#[test]
+fn test_complex_key() {
+    #[allow(unused)]
+    #[derive(SqlSchema)]
+    struct FieldValue {
+        #[batiskaf(primary_key)]
+        x: i64,
+        #[batiskaf(primary_key)]
+        y: i64,
+        value: f64,
+    }
+    let sql = FieldValue::create_table_statement("height");
+    assert_eq!(
+        "CREATE TABLE height (x INTEGER NOT NULL, y INTEGER NOT NULL, value REAL NOT NULL, PRIMARY KEY (x, y))",
+        sql
+    );
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute(&sql, []).unwrap();
+}
+
+#[test]
+fn test_constraints() {
+    #[allow(unused)]
+    #[derive(SqlSchema)]
+    struct Account {
+        #[batiskaf(primary_key)]
+        login: String,
+        #[batiskaf(unique)]
+        email: String,
+        #[batiskaf(sql_default = "1")]
+        active: bool,
+        #[batiskaf(check = "balance >= 0", sql_default = "0")]
+        balance: i64,
+        #[batiskaf(sql_type = "NUMERIC")]
+        rating: Option<Rating>,
+    }
+    #[allow(unused)]
+    struct Rating(f64);
+    let sql = Account::create_table_statement("account");
+    assert_eq!(
+        "CREATE TABLE account (login TEXT PRIMARY KEY NOT NULL, email TEXT NOT NULL UNIQUE, active INTEGER NOT NULL DEFAULT 1, balance INTEGER NOT NULL DEFAULT 0 CHECK (balance >= 0), rating NUMERIC)",
+        sql
+    );
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute(&sql, []).unwrap();
+    conn.execute(
+        "insert into account (login, email) values ('bob', 'bob@example.com')",
+        [],
+    )
+    .unwrap();
+    let active: bool = conn
+        .query_row("select active from account", [], |row| row.get(0))
+        .unwrap();
+    assert!(active);
+    assert!(conn.execute("update account set balance = -1", []).is_err());
+}
+
+#[test]
+fn test_braces() {
+    #[allow(unused)]
+    #[derive(SqlSchema)]
+    struct Settings {
+        #[batiskaf(primary_key)]
+        id: i64,
+        #[batiskaf(sql_default = "'{}'", check = "value <> '{x}'")]
+        value: String,
+    }
+    let sql = Settings::create_table_statement("settings");
+    assert_eq!(
+        "CREATE TABLE settings (id INTEGER PRIMARY KEY, value TEXT NOT NULL DEFAULT '{}' CHECK (value <> '{x}'))",
+        sql
+    );
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute(&sql, []).unwrap();
+    conn.execute("insert into settings (id) values (1)", [])
+        .unwrap();
+    let value: String = conn
+        .query_row("select value from settings", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!("{}", value);
+}
+
+#[test]
+fn test_verify_mapping() {
+    #[allow(unused)]
+    #[derive(SqlSchema)]
+    struct Person {
+        #[batiskaf(primary_key, autogenerated)]
+        id: i64,
+        #[batiskaf(column = "full_name")]
+        name: String,
+        age: Option<u32>,
+        #[batiskaf(skip)]
+        hobby: Option<String>,
+    }
+    assert_eq!(
+        vec![
+            ColumnDef {
+                name: "id".to_string(),
+                nullable: false,
+                primary_key: true,
+            },
+            ColumnDef {
+                name: "full_name".to_string(),
+                nullable: false,
+                primary_key: false,
+            },
+            ColumnDef {
+                name: "age".to_string(),
+                nullable: true,
+                primary_key: false,
+            },
+        ],
+        Person::columns()
+    );
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute(&Person::create_table_statement("person"), [])
+        .unwrap();
+    assert!(conn.verify_mapping::<Person>("person").unwrap().is_ok());
+    conn.execute("alter table person add column email text", [])
+        .unwrap();
+    let report = conn.verify_mapping::<Person>("person").unwrap();
+    assert!(!report.is_ok());
+    assert_eq!(
+        "table `person`:\n- column `email` is not mapped to any field",
+        report.to_string()
+    );
+}
+
+#[test]
+fn test_verify_mapping_optional_key() {
+    // ключ None до вставки, затем значение, назначенное SQLite
+    #[allow(unused)]
+    #[derive(SqlSchema)]
+    struct Note {
+        #[batiskaf(primary_key, autogenerated)]
+        id: Option<i64>,
+        text: String,
+    }
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "create table note (id integer primary key autoincrement, text text not null);
+         create table strict_note (id integer not null primary key, text text not null);",
+    )
+    .unwrap();
+    assert!(conn.verify_mapping::<Note>("note").unwrap().is_ok());
+    assert!(conn.verify_mapping::<Note>("strict_note").unwrap().is_ok());
+}
@@ -0,0 +1,105 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rusqlite::Connection;
+use rust_decimal::Decimal;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use batiskaf::{BatiskafConnection, SqlSchema};
+use batiskaf_derive::*;
+
+#[derive(Debug, PartialEq, SqlParam, SqlResult, SqlInsert, SqlSchema)]
+struct Payment {
+    #[batiskaf(primary_key, autogenerated)]
+    pub id: i64,
+    #[batiskaf(uuid = "blob")]
+    pub uid: Uuid,
+    #[batiskaf(uuid = "text")]
+    pub parent: Option<Uuid>,
+    #[batiskaf(timestamp = "unix")]
+    pub created: DateTime<Utc>,
+    #[batiskaf(timestamp = "unix_ms")]
+    pub processed: Option<OffsetDateTime>,
+    #[batiskaf(timestamp = "rfc3339")]
+    pub due: NaiveDateTime,
+    #[batiskaf(decimal = "cents")]
+    pub amount: Decimal,
+    #[batiskaf(decimal = "text")]
+    pub rate: Decimal,
+}
+
+fn payment() -> Payment {
+    Payment {
+        id: 0,
+        uid: Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef),
+        parent: None,
+        created: DateTime::from_timestamp(1_600_000_000, 0).unwrap(),
+        processed: Some(
+            OffsetDateTime::from_unix_timestamp_nanos(1_600_000_000_123_000_000).unwrap(),
+        ),
+        due: DateTime::from_timestamp(1_600_086_400, 500_000_000)
+            .unwrap()
+            .naive_utc(),
+        amount: Decimal::from_str("12.34").unwrap(),
+        rate: Decimal::from_str("0.0725").unwrap(),
+    }
+}
+
+#[test]
+fn test_storage() {
+    let conn = Connection::open_in_memory().unwrap();
+    let ddl = Payment::create_table_statement("payment");
+    assert!(ddl.contains("uid BLOB NOT NULL"));
+    assert!(ddl.contains("parent TEXT,"));
+    assert!(ddl.contains("created INTEGER NOT NULL"));
+    assert!(ddl.contains("due TEXT NOT NULL"));
+    assert!(ddl.contains("amount INTEGER NOT NULL"));
+    conn.execute_batch(&ddl).unwrap();
+    let mut p = payment();
+    p.id = conn.insert("payment", &p).unwrap();
+    let row: (Vec<u8>, Option<String>, i64, i64, String, i64, String) = conn
+        .query_row(
+            "select uid, parent, created, processed, due, amount, rate from payment",
+            [],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get(6)?,
+                ))
+            },
+        )
+        .unwrap();
+    assert_eq!(16, row.0.len());
+    assert_eq!(None, row.1);
+    assert_eq!(1_600_000_000, row.2);
+    assert_eq!(1_600_000_000_123, row.3);
+    assert_eq!("2020-09-14T12:26:40.500Z", row.4);
+    assert_eq!(1234, row.5);
+    assert_eq!("0.0725", row.6);
+    let stored: Payment = conn
+        .select_one(
+            "select id, uid, parent, created, processed, due, amount, rate from payment",
+            &[],
+        )
+        .unwrap();
+    assert_eq!(p, stored);
+}
+
+#[test]
+fn test_storage_error() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(&Payment::create_table_statement("payment"))
+        .unwrap();
+    let mut p = payment();
+    p.amount = Decimal::from_str("0.001").unwrap();
+    match conn.insert("payment", &p) {
+        Err(rusqlite::Error::ToSqlConversionFailure(_)) => (),
+        x => panic!("unexpected result: {:?}", x),
+    }
+}
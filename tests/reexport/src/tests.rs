@@ -0,0 +1,22 @@
+use batiskaf::rusqlite::Connection;
+use batiskaf::{BatiskafConnection, DynRow};
+
+#[test]
+fn test_params_macro() {
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "create table person (id integer primary key, name text not null, age integer);
+         insert into person (name, age) values ('Alice', 33), ('Bob', 30);",
+    )
+    .unwrap();
+    let name = "Bob".to_string();
+    let people: Vec<DynRow> = conn
+        .select_many(
+            "select id, name, age from person where name = :name and age >= :age",
+            &batiskaf::params! { name => &name, age => 30 },
+        )
+        .unwrap();
+    assert_eq!(1, people.len());
+    assert_eq!(2, people[0].get::<i64>("id").unwrap());
+    assert_eq!(name, people[0].get::<String>("name").unwrap());
+}
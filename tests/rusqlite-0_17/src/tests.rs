@@ -0,0 +1,310 @@
+use rusqlite::types::ToSql;
+use rusqlite::Connection;
+
+use batiskaf::migrate::Migrator;
+use batiskaf::*;
+
+#[derive(Debug, PartialEq, SqlParam, SqlResult, SqlInsert, SqlUpdate, SqlDelete, SqlSchema)]
+struct Person {
+    #[batiskaf(primary_key, autogenerated)]
+    id: i64,
+    name: String,
+    age: Option<u32>,
+}
+
+fn create_table(conn: &Connection) {
+    conn.execute_batch(&Person::create_table_statement("person"))
+        .unwrap();
+}
+
+fn person(name: &str, age: Option<u32>) -> Person {
+    Person {
+        id: 0,
+        name: name.to_string(),
+        age,
+    }
+}
+
+fn count(conn: &Connection) -> i64 {
+    backend::query_row(conn, "select count(*) from person", &[], |row| row.get(0)).unwrap()
+}
+
+#[test]
+fn test_crud() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    let mut alice = person("Alice", None);
+    alice.id = conn.insert("person", &alice).unwrap();
+    alice.age = Some(33);
+    assert_eq!(1, conn.update("person", &alice).unwrap());
+    let x: Person = conn
+        .select_one(
+            "select id, name, age from person where id = :id",
+            &[(":id", &alice.id as &dyn ToSql)],
+        )
+        .unwrap();
+    assert_eq!(alice, x);
+    assert_eq!(1, conn.delete("person", &alice).unwrap());
+    assert_eq!(0, count(&conn));
+}
+
+#[test]
+fn test_select_with() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    for (name, age) in &[("Alice", Some(33)), ("Bob", Some(30)), ("Carol", None)] {
+        conn.insert("person", &person(name, *age)).unwrap();
+    }
+    let people: Vec<Person> = conn
+        .select_many_with(
+            "select id, name, age from person where age >= :age order by id",
+            &params! { age => 31 },
+        )
+        .unwrap();
+    assert_eq!(vec!["Alice"], names(&people));
+    let bob: Person = conn
+        .select_one_with(
+            "select id, name, age from person where name = :name",
+            &params! { name => "Bob" },
+        )
+        .unwrap();
+    assert_eq!(Some(30), bob.age);
+    let changes = conn
+        .execute_with(
+            "update person set age = :age where age is null",
+            &params! { age => 40 },
+        )
+        .unwrap();
+    assert_eq!(1, changes);
+}
+
+#[test]
+fn test_dynamic_sql() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    for (name, age) in &[("Alice", Some(33)), ("Bob", Some(30)), ("Carol", None)] {
+        conn.insert("person", &person(name, *age)).unwrap();
+    }
+    let ids = vec![1, 3];
+    let mut sql = DynamicSql::new("select id, name, age from person");
+    sql.where_clause(|w| {
+        w.push_in("id in (:ids)", ":ids", &ids);
+    })
+    .push("order by id");
+    let people: Vec<Person> = conn.select_many(&sql.sql(), &sql.params()).unwrap();
+    assert_eq!(vec!["Alice", "Carol"], names(&people));
+}
+
+#[test]
+fn test_migrate() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    let migrations = [
+        (
+            "create_person",
+            "create table person (id integer primary key)",
+        ),
+        ("add_name", "alter table person add column name text"),
+    ];
+    let migrator = Migrator::new(&migrations);
+    assert_eq!(
+        vec!["create_person", "add_name"],
+        migrator.run(&mut conn).unwrap()
+    );
+    assert_eq!(2, migrator.version(&conn).unwrap());
+}
+
+#[test]
+fn test_verify_mapping() {
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    assert!(conn.verify_mapping::<Person>("person").unwrap().is_ok());
+}
+
+#[test]
+fn test_pool() {
+    let pool = batiskaf::pool::Pool::builder(Connection::open_in_memory)
+        .max_size(1)
+        .build();
+    let conn = pool.get().unwrap();
+    create_table(&conn);
+    conn.insert("person", &person("Alice", None)).unwrap();
+    assert_eq!(1, count(&conn));
+}
+
+#[test]
+fn test_async_connection() {
+    let conn = block_on(AsyncConnection::open_in_memory()).unwrap();
+    block_on(conn.call(|conn| {
+        create_table(conn);
+        Ok(())
+    }))
+    .unwrap();
+    let id = block_on(conn.insert("person", person("Alice", Some(33)))).unwrap();
+    let name = "Alice".to_string();
+    let alice: Person = block_on(conn.select_one(
+        "select id, name, age from person where name = :name",
+        &[(":name", &name)],
+    ))
+    .unwrap();
+    assert_eq!(id, alice.id);
+}
+
+mod comma_separated {
+    use rusqlite::types::{FromSqlResult, ToSqlOutput, Value, ValueRef};
+
+    pub fn from_sql(value: ValueRef<'_>) -> FromSqlResult<Vec<String>> {
+        Ok(value.as_str()?.split(',').map(|x| x.to_string()).collect())
+    }
+
+    pub fn to_sql(value: &[String]) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Owned(Value::Text(value.join(","))))
+    }
+}
+
+#[test]
+fn test_conversion() {
+    #[derive(Debug, PartialEq, SqlParam, SqlResult)]
+    struct Host {
+        id: i64,
+        #[batiskaf(with = "comma_separated")]
+        tags: Vec<String>,
+    }
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch("create table host (id integer primary key, tags)")
+        .unwrap();
+    let host = Host {
+        id: 1,
+        tags: vec!["a".to_string(), "b".to_string()],
+    };
+    conn.execute_with("insert into host (id, tags) values (:id, :tags)", &host)
+        .unwrap();
+    let x: Host = conn.select_one("select id, tags from host", &[]).unwrap();
+    assert_eq!(host, x);
+    // индекс столбца в ошибке преобразования - индекс в результате запроса
+    let err = conn
+        .select_one::<Host>("select 2 as id, 3 as tags", &[])
+        .unwrap_err();
+    match err {
+        rusqlite::Error::InvalidColumnType(1, rusqlite::types::Type::Integer) => {}
+        err => panic!("unexpected error: {:?}", err),
+    }
+}
+
+#[test]
+fn test_deny_unknown_columns() {
+    use batiskaf::convert::UnknownColumns;
+
+    #[derive(Debug, SqlResult)]
+    #[batiskaf(deny_unknown_columns)]
+    struct Name {
+        #[allow(unused)]
+        name: String,
+    }
+
+    fn unknown<T: std::fmt::Debug>(result: rusqlite::Result<T>) -> Vec<String> {
+        match result {
+            Err(rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Null, err)) => {
+                err.downcast_ref::<UnknownColumns>()
+                    .unwrap()
+                    .columns
+                    .clone()
+            }
+            x => panic!("unexpected result: {:?}", x),
+        }
+    }
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    conn.insert("person", &person("Alice", Some(33))).unwrap();
+    let sql = "select * from person";
+    assert_eq!(
+        vec!["id", "age"],
+        unknown(conn.select_one::<Name>(sql, &[]))
+    );
+    assert_eq!(
+        vec!["id", "age"],
+        unknown(conn.select_one_with::<Name, _>(sql, &params! {}))
+    );
+    assert_eq!(
+        vec!["id", "age"],
+        unknown(backend::query_row(&conn, sql, &[], Name::from_row))
+    );
+}
+
+#[test]
+fn test_dyn_row() {
+    use rusqlite::types::Value;
+
+    let conn = Connection::open_in_memory().unwrap();
+    create_table(&conn);
+    conn.execute_batch("insert into person (name, age) values ('Alice', 33), ('Bob', null)")
+        .unwrap();
+    let rows: Vec<DynRow> = conn
+        .select_many("select name, age from person order by id", &[])
+        .unwrap();
+    assert_eq!(&["name", "age"], rows[0].columns());
+    assert_eq!(
+        &[Value::Text("Bob".to_string()), Value::Null],
+        rows[1].values()
+    );
+    assert_eq!(Some(33), rows[0].get::<Option<u32>>("age").unwrap());
+    let row: DynRow = conn
+        .select_one("select count(*) as n from person", &[])
+        .unwrap();
+    assert_eq!(2, row.get::<i64>("n").unwrap());
+}
+
+#[test]
+fn test_rest() {
+    use std::collections::BTreeMap;
+
+    use rusqlite::types::Value;
+
+    #[derive(Debug, PartialEq, SqlResult)]
+    struct Sales {
+        region: String,
+        #[batiskaf(rest)]
+        months: BTreeMap<String, Value>,
+    }
+    let conn = Connection::open_in_memory().unwrap();
+    let sql = "select 'north' as region, 10 as jan, 20 as feb";
+    let months: BTreeMap<String, Value> = vec![
+        ("jan".to_string(), Value::Integer(10)),
+        ("feb".to_string(), Value::Integer(20)),
+    ]
+    .into_iter()
+    .collect();
+    let sales = Sales {
+        region: "north".to_string(),
+        months,
+    };
+    assert_eq!(sales, conn.select_one(sql, &[]).unwrap());
+    assert_eq!(vec![sales], conn.select_many::<Sales>(sql, &[]).unwrap());
+}
+
+fn names(people: &[Person]) -> Vec<&str> {
+    people.iter().map(|p| p.name.as_str()).collect()
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+    use std::thread::{self, Thread};
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}